@@ -4,7 +4,7 @@ use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use tauri::Manager;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub id: String,
     pub name: String,
@@ -14,6 +14,7 @@ pub struct Game {
     pub cover_image: Option<String>,
     pub save_mode: Option<String>,  // 存档模式：single_file, folder, file_group, container
     pub save_config: Option<String>,  // JSON 配置字符串
+    pub paused: bool,  // 暂停自动快照
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +26,37 @@ pub struct Snapshot {
     pub backup_save_path: String,
     pub note: Option<String>,
     pub created_at: String,
+    pub thumbnail_path: Option<String>,  // 存档缩略图（如 file_group 模式下的图片附属文件）
+    pub kind: Option<String>,  // 快照类型：auto, pre_restore, manual, startup, resume, tombstone
+    pub summary: Option<String>,  // 存档内容摘要（如 RPG Maker 存档的地图、游戏时间和队伍）
+}
+
+/// 快照中单个存档文件的记录，用于判断存档内容是否有变化
+#[derive(Debug)]
+pub struct SnapshotFileRecord {
+    pub original_path: String,
+    pub relative_path: String,
+    /// 相对于快照目录的备份路径
+    pub backup_file: String,
+    pub size: i64,
+    pub modified: Option<String>,
+    pub sha256: String,
+}
+
+/// 某个存档文件最近一次被快照记录的情况
+#[derive(Debug)]
+pub struct RecordedFile {
+    pub snapshot_id: String,
+    pub kind: Option<String>,
+    /// 所在快照的目录
+    pub backup_save_path: String,
+    pub original_path: String,
+    pub relative_path: String,
+    /// 早期的记录没有保存备份路径
+    pub backup_file: Option<String>,
+    pub size: i64,
+    pub modified: Option<String>,
+    pub sha256: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -99,6 +131,20 @@ impl Database {
             [],
         );
 
+        let _ = conn.execute(
+            "ALTER TABLE games ADD COLUMN paused INTEGER NOT NULL DEFAULT 0",
+            [],
+        );
+
+        // 应用级设置（如全局暂停）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         let table_exists = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='snapshots'")?.exists([])?;
         
         if table_exists {
@@ -175,6 +221,48 @@ impl Database {
             )?;
         }
 
+        let _ = conn.execute(
+            "ALTER TABLE snapshots ADD COLUMN thumbnail_path TEXT",
+            [],
+        );
+
+        let _ = conn.execute(
+            "ALTER TABLE snapshots ADD COLUMN kind TEXT",
+            [],
+        );
+
+        let _ = conn.execute(
+            "ALTER TABLE snapshots ADD COLUMN summary TEXT",
+            [],
+        );
+
+        // 快照中每个文件的内容哈希
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS snapshot_files (
+                snapshot_id TEXT NOT NULL,
+                original_path TEXT NOT NULL,
+                relative_path TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                sha256 TEXT NOT NULL,
+                FOREIGN KEY(snapshot_id) REFERENCES snapshots(id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_snapshot_files_original_path ON snapshot_files(original_path)",
+            [],
+        )?;
+
+        let _ = conn.execute(
+            "ALTER TABLE snapshot_files ADD COLUMN backup_file TEXT",
+            [],
+        );
+
+        let _ = conn.execute(
+            "ALTER TABLE snapshot_files ADD COLUMN modified TEXT",
+            [],
+        );
+
         let screenshots_table_exists = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='screenshots'")?.exists([])?;
         
         if screenshots_table_exists {
@@ -256,7 +344,7 @@ impl Database {
 
     pub fn get_games(&self) -> Result<Vec<Game>> {
         let conn = self.connect()?;
-        let mut stmt = conn.prepare("SELECT id, name, exe_path, COALESCE(game_folder_path, save_folder_path, '') as game_folder_path, save_folder_path, cover_image, save_mode, save_config FROM games")?;
+        let mut stmt = conn.prepare("SELECT id, name, exe_path, COALESCE(game_folder_path, save_folder_path, '') as game_folder_path, save_folder_path, cover_image, save_mode, save_config, paused FROM games")?;
         let game_iter = stmt.query_map([], |row| {
             Ok(Game {
                 id: row.get(0)?,
@@ -267,6 +355,7 @@ impl Database {
                 cover_image: row.get(5)?,
                 save_mode: row.get(6)?,
                 save_config: row.get(7)?,
+                paused: row.get(8)?,
             })
         })?;
 
@@ -280,8 +369,8 @@ impl Database {
     pub fn add_snapshot(&self, snapshot: &Snapshot) -> Result<()> {
         let conn = self.connect()?;
         conn.execute(
-            "INSERT INTO snapshots (id, game_id, name, original_save_path, backup_save_path, note, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO snapshots (id, game_id, name, original_save_path, backup_save_path, note, created_at, thumbnail_path, kind, summary)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 snapshot.id,
                 snapshot.game_id,
@@ -289,15 +378,68 @@ impl Database {
                 snapshot.original_save_path,
                 snapshot.backup_save_path,
                 snapshot.note,
-                snapshot.created_at
+                snapshot.created_at,
+                snapshot.thumbnail_path,
+                snapshot.kind,
+                snapshot.summary
             ],
         )?;
         Ok(())
     }
 
+    pub fn add_snapshot_files(&self, snapshot_id: &str, files: &[SnapshotFileRecord]) -> Result<()> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction()?;
+        for file in files {
+            tx.execute(
+                "INSERT INTO snapshot_files (snapshot_id, original_path, relative_path, backup_file, size, modified, sha256)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![snapshot_id, file.original_path, file.relative_path, file.backup_file, file.size, file.modified, file.sha256],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// 每个存档文件在最近一次快照中的记录（不含恢复前快照，包含墓碑记录）。
+    /// `under` 不为空时只返回该路径本身及其下的文件
+    pub fn get_latest_file_records(&self, game_id: &str, under: Option<&str>) -> Result<Vec<RecordedFile>> {
+        let conn = self.connect()?;
+        let under_prefix = under.map(|p| format!("{}{}", p.trim_end_matches(['/', '\\']), std::path::MAIN_SEPARATOR));
+        let mut stmt = conn.prepare(
+            "SELECT snapshot_id, kind, backup_save_path, original_path, relative_path, backup_file, size, modified, sha256 FROM (
+                SELECT f.snapshot_id, s.kind, s.backup_save_path, f.original_path, f.relative_path, f.backup_file, f.size, f.modified, f.sha256,
+                       ROW_NUMBER() OVER (PARTITION BY f.original_path ORDER BY s.created_at DESC) AS position
+                FROM snapshot_files f
+                JOIN snapshots s ON s.id = f.snapshot_id
+                WHERE s.game_id = ?1 AND COALESCE(s.kind, 'auto') != 'pre_restore'
+                  AND (?2 IS NULL OR f.original_path = ?2 OR substr(f.original_path, 1, length(?3)) = ?3)
+             )
+             WHERE position = 1",
+        )?;
+        let record_iter = stmt.query_map(params![game_id, under, under_prefix], |row| {
+            Ok(RecordedFile {
+                snapshot_id: row.get(0)?,
+                kind: row.get(1)?,
+                backup_save_path: row.get(2)?,
+                original_path: row.get(3)?,
+                relative_path: row.get(4)?,
+                backup_file: row.get(5)?,
+                size: row.get(6)?,
+                modified: row.get(7)?,
+                sha256: row.get(8)?,
+            })
+        })?;
+
+        let mut records = Vec::new();
+        for record in record_iter {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
     pub fn get_snapshots(&self, game_id: &str) -> Result<Vec<Snapshot>> {
         let conn = self.connect()?;
-        let mut stmt = conn.prepare("SELECT id, game_id, name, original_save_path, backup_save_path, note, created_at FROM snapshots WHERE game_id = ?1 ORDER BY created_at DESC")?;
+        let mut stmt = conn.prepare("SELECT id, game_id, name, original_save_path, backup_save_path, note, created_at, thumbnail_path, kind, summary FROM snapshots WHERE game_id = ?1 ORDER BY created_at DESC")?;
         let snapshot_iter = stmt.query_map([game_id], |row| {
             Ok(Snapshot {
                 id: row.get(0)?,
@@ -307,6 +449,9 @@ impl Database {
                 backup_save_path: row.get(4)?,
                 note: row.get(5)?,
                 created_at: row.get(6)?,
+                thumbnail_path: row.get(7)?,
+                kind: row.get(8)?,
+                summary: row.get(9)?,
             })
         })?;
 
@@ -337,7 +482,7 @@ impl Database {
 
     pub fn get_snapshot(&self, snapshot_id: &str) -> Result<Snapshot> {
         let conn = self.connect()?;
-        let mut stmt = conn.prepare("SELECT id, game_id, name, original_save_path, backup_save_path, note, created_at FROM snapshots WHERE id = ?1")?;
+        let mut stmt = conn.prepare("SELECT id, game_id, name, original_save_path, backup_save_path, note, created_at, thumbnail_path, kind, summary FROM snapshots WHERE id = ?1")?;
         let snapshot = stmt.query_row([snapshot_id], |row| {
             Ok(Snapshot {
                 id: row.get(0)?,
@@ -347,6 +492,9 @@ impl Database {
                 backup_save_path: row.get(4)?,
                 note: row.get(5)?,
                 created_at: row.get(6)?,
+                thumbnail_path: row.get(7)?,
+                kind: row.get(8)?,
+                summary: row.get(9)?,
             })
         })?;
         Ok(snapshot)
@@ -398,9 +546,43 @@ impl Database {
         Ok(())
     }
 
+    pub fn set_game_paused(&self, game_id: &str, paused: bool) -> Result<()> {
+        let conn = self.connect()?;
+        conn.execute("UPDATE games SET paused = ?1 WHERE id = ?2", params![paused, game_id])?;
+        Ok(())
+    }
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.connect()?;
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+        let mut rows = stmt.query([key])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        let conn = self.connect()?;
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    /// 是否暂停了所有游戏的自动快照
+    pub fn get_watching_paused(&self) -> Result<bool> {
+        Ok(self.get_setting("watching_paused")?.as_deref() == Some("1"))
+    }
+
+    pub fn set_watching_paused(&self, paused: bool) -> Result<()> {
+        self.set_setting("watching_paused", if paused { "1" } else { "0" })
+    }
+
     pub fn get_game(&self, game_id: &str) -> Result<Game> {
         let conn = self.connect()?;
-        let mut stmt = conn.prepare("SELECT id, name, exe_path, game_folder_path, save_folder_path, cover_image, save_mode, save_config FROM games WHERE id = ?1")?;
+        let mut stmt = conn.prepare("SELECT id, name, exe_path, game_folder_path, save_folder_path, cover_image, save_mode, save_config, paused FROM games WHERE id = ?1")?;
         let game = stmt.query_row([game_id], |row| {
             Ok(Game {
                 id: row.get(0)?,
@@ -411,6 +593,7 @@ impl Database {
                 cover_image: row.get(5)?,
                 save_mode: row.get(6)?,
                 save_config: row.get(7)?,
+                paused: row.get(8)?,
             })
         })?;
         Ok(game)
@@ -457,6 +640,7 @@ impl Database {
         
         // Delete from database
         let conn = self.connect()?;
+        conn.execute("DELETE FROM snapshot_files WHERE snapshot_id IN (SELECT id FROM snapshots WHERE game_id = ?1)", [game_id])?;
         conn.execute("DELETE FROM snapshots WHERE game_id = ?1", [game_id])?;
         conn.execute("DELETE FROM screenshots WHERE game_id = ?1", [game_id])?;
         conn.execute("DELETE FROM games WHERE id = ?1", [game_id])?;
@@ -465,6 +649,7 @@ impl Database {
 
     pub fn delete_snapshot(&self, snapshot_id: &str) -> Result<()> {
         let conn = self.connect()?;
+        conn.execute("DELETE FROM snapshot_files WHERE snapshot_id = ?1", [snapshot_id])?;
         conn.execute("DELETE FROM snapshots WHERE id = ?1", [snapshot_id])?;
         Ok(())
     }
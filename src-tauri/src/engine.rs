@@ -0,0 +1,170 @@
+use crate::save_config::SaveConfig;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 游戏目录中用于识别引擎的特征
+enum Marker {
+    /// 相对于游戏目录的文件或文件夹
+    Path(&'static str),
+    /// 游戏目录下带有该扩展名的文件
+    Extension(&'static str),
+    /// 游戏目录下文件名包含该字符串的文件（不区分大小写）
+    NameContains(&'static str),
+}
+
+/// 内置的引擎预设
+struct EnginePreset {
+    id: &'static str,
+    name: &'static str,
+    markers: &'static [Marker],
+    save_mode: &'static str,
+    save_config: &'static str,
+    /// 默认存档位置（相对于游戏目录），按顺序取第一个存在的
+    save_dirs: &'static [&'static str],
+}
+
+/// 按顺序匹配，特征更明确的引擎排在前面
+const PRESETS: &[EnginePreset] = &[
+    EnginePreset {
+        id: "rpgmaker_mz",
+        name: "RPG Maker MZ",
+        markers: &[Marker::Path("js/rmmz_core.js"), Marker::Path("www/js/rmmz_core.js")],
+        save_mode: "single_file",
+        save_config: r#"{"extensions": ["rmmzsave"], "exclude_globs": ["config.rmmzsave", "global.rmmzsave"]}"#,
+        save_dirs: &["save", "www/save"],
+    },
+    EnginePreset {
+        id: "rpgmaker_mv",
+        name: "RPG Maker MV",
+        markers: &[Marker::Path("www/js/rpg_core.js"), Marker::Path("js/rpg_core.js")],
+        save_mode: "single_file",
+        save_config: r#"{"extensions": ["rpgsave"], "exclude_globs": ["config.rpgsave", "global.rpgsave"]}"#,
+        save_dirs: &["www/save", "save"],
+    },
+    EnginePreset {
+        id: "renpy",
+        name: "Ren'Py",
+        markers: &[Marker::Path("renpy"), Marker::Path("game/script_version.txt")],
+        save_mode: "container",
+        save_config: r#"{"container_extensions": ["save"]}"#,
+        save_dirs: &["game/saves"],
+    },
+    EnginePreset {
+        id: "tyrano",
+        name: "TyranoScript",
+        markers: &[Marker::Path("tyrano"), Marker::Path("resources/app/tyrano")],
+        save_mode: "single_file",
+        save_config: r#"{"extensions": ["sav"], "exclude_globs": ["*_sf.sav"]}"#,
+        save_dirs: &["savedata", "resources/app/savedata"],
+    },
+    EnginePreset {
+        id: "kirikiri",
+        name: "KiriKiri",
+        markers: &[Marker::Path("data.xp3"), Marker::NameContains("krkr"), Marker::Extension("xp3")],
+        save_mode: "file_group",
+        save_config: r#"{"extensions": ["kdt", "ksd"], "exclude_globs": ["datasc.ksd", "datasu.ksd"]}"#,
+        save_dirs: &["savedata"],
+    },
+    EnginePreset {
+        id: "siglus",
+        name: "SiglusEngine",
+        markers: &[Marker::Path("Scene.pck"), Marker::Path("Gameexe.dat"), Marker::NameContains("siglus")],
+        save_mode: "folder",
+        save_config: "{}",
+        save_dirs: &["savedata"],
+    },
+    EnginePreset {
+        id: "artemis",
+        name: "Artemis",
+        markers: &[Marker::Path("root.pfs"), Marker::NameContains("artemis"), Marker::Extension("pfs")],
+        save_mode: "folder",
+        save_config: "{}",
+        save_dirs: &["save", "savedata"],
+    },
+    EnginePreset {
+        id: "nscripter",
+        name: "NScripter",
+        // 明文脚本 0.txt / 00.txt 很常见，不足以单独识别 NScripter
+        markers: &[Marker::Path("nscript.dat"), Marker::Extension("nsa")],
+        save_mode: "single_file",
+        save_config: r#"{"extensions": ["dat"], "include_globs": ["save*.dat"]}"#,
+        save_dirs: &["savedata", "."],
+    },
+];
+
+/// 检测到的引擎及其预设配置
+#[derive(Debug, Serialize)]
+pub struct DetectedEngine {
+    pub engine: String,
+    pub name: String,
+    /// 命中的特征，便于用户判断检测是否可信
+    pub evidence: String,
+    pub save_mode: String,
+    pub save_config: String,
+    /// 找到的默认存档文件夹；不存在时为 None
+    pub save_folder_path: Option<String>,
+}
+
+fn find_marker(game_folder: &Path, top_level: &[String], marker: &Marker) -> Option<String> {
+    match marker {
+        Marker::Path(rel) => game_folder.join(rel).exists().then(|| rel.to_string()),
+        Marker::Extension(ext) => top_level
+            .iter()
+            .find(|name| name.to_lowercase().ends_with(&format!(".{}", ext)))
+            .cloned(),
+        Marker::NameContains(part) => top_level.iter().find(|name| name.to_lowercase().contains(part)).cloned(),
+    }
+}
+
+/// Ren'Py 游戏通常把存档放在 %APPDATA%/RenPy/<config.save_directory>
+fn renpy_appdata_saves(game_folder: &Path) -> Option<PathBuf> {
+    let options = fs::read_to_string(game_folder.join("game").join("options.rpy")).ok()?;
+    let line = options
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| l.starts_with("define config.save_directory") || l.starts_with("config.save_directory"))?;
+    let value = line.split_once('=')?.1.trim().trim_matches(|c| c == '"' || c == '\'');
+    if value.is_empty() || value == "None" {
+        return None;
+    }
+    let dir = PathBuf::from(std::env::var_os("APPDATA")?).join("RenPy").join(value);
+    dir.is_dir().then_some(dir)
+}
+
+fn default_save_folder(game_folder: &Path, preset: &EnginePreset) -> Option<PathBuf> {
+    if preset.id == "renpy" {
+        if let Some(dir) = renpy_appdata_saves(game_folder) {
+            return Some(dir);
+        }
+    }
+    preset
+        .save_dirs
+        .iter()
+        .map(|rel| if *rel == "." { game_folder.to_path_buf() } else { game_folder.join(rel) })
+        .find(|dir| dir.is_dir())
+}
+
+/// 根据游戏目录中的特征文件识别引擎；无法识别时返回 None
+pub fn detect_engine(game_folder: &Path) -> Option<DetectedEngine> {
+    let top_level: Vec<String> = fs::read_dir(game_folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+
+    PRESETS.iter().find_map(|preset| {
+        let evidence = preset.markers.iter().find_map(|m| find_marker(game_folder, &top_level, m))?;
+        // 预设是内置的，解析失败说明预设本身有误，跳过而不是给出无效配置
+        let config = SaveConfig::parse(preset.save_mode, preset.save_config).ok()?;
+        Some(DetectedEngine {
+            engine: preset.id.to_string(),
+            name: preset.name.to_string(),
+            evidence,
+            save_mode: preset.save_mode.to_string(),
+            save_config: config.to_json(),
+            save_folder_path: default_save_folder(game_folder, preset).map(|p| p.to_string_lossy().to_string()),
+        })
+    })
+}
@@ -5,15 +5,25 @@ mod db;
 mod watcher;
 mod snapshot;
 mod screenshot;
+mod path_index;
+mod restore;
+mod save_config;
+mod save_rules;
+mod observe;
+mod engine;
+mod renpy;
+mod rpgmaker;
 
 use db::{Database, Game, Snapshot, Screenshot};
+use save_config::SaveConfig;
 use snapshot::SnapshotManager;
+use observe::SaveObserver;
 use screenshot::ScreenshotManager;
-use watcher::SaveWatcher;
+use watcher::{EventBatcher, SaveWatcher, WatchBackend, WatchStatus, WriteSuppressor};
 
 #[cfg(target_os = "windows")]
 use global_hotkey::{
-    hotkey::{HotKey, Code},
+    hotkey::{HotKey, Code, Modifiers},
     GlobalHotKeyManager,
 };
 
@@ -21,6 +31,8 @@ use global_hotkey::{
 struct AppState {
     db: Database,
     watcher: Arc<Mutex<SaveWatcher>>,
+    write_suppressor: WriteSuppressor,
+    observer: SaveObserver,
     snapshot_manager: Arc<Mutex<SnapshotManager>>,
     screenshot_manager: Arc<Mutex<ScreenshotManager>>,
     hotkey_manager: Arc<Mutex<Option<GlobalHotKeyManager>>>,
@@ -30,10 +42,25 @@ struct AppState {
 struct AppState {
     db: Database,
     watcher: Arc<Mutex<SaveWatcher>>,
+    write_suppressor: WriteSuppressor,
+    observer: SaveObserver,
     snapshot_manager: Arc<Mutex<SnapshotManager>>,
     screenshot_manager: Arc<Mutex<ScreenshotManager>>,
 }
 
+/// How long watcher events on paths written by a restore are ignored after the write
+const RESTORE_SUPPRESS_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Rebuild the snapshot manager's path-to-game index after games change
+fn refresh_path_index(state: &State<AppState>) -> Result<(), String> {
+    state
+        .snapshot_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock snapshot_manager: {}", e))?
+        .refresh_path_index()
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn add_game(
     _app_handle: tauri::AppHandle,
@@ -59,6 +86,22 @@ fn add_game(
     if !Path::new(&exe_path).exists() {
         return Err(format!("游戏执行文件不存在: {}", exe_path));
     }
+
+    // Callers that send neither a mode nor a config get the detected engine's preset as a whole,
+    // so a supplied config is never paired with a preset's mode
+    let (save_mode, save_config) = match (save_mode, save_config) {
+        (None, None) => match engine::detect_engine(Path::new(&game_folder_path)) {
+            Some(preset) => (preset.save_mode, Some(preset.save_config)),
+            None => ("single_file".to_string(), None),
+        },
+        (save_mode, save_config) => (save_mode.unwrap_or_else(|| "single_file".to_string()), save_config),
+    };
+
+    // Store the config in its normalized, versioned form
+    let config = match save_config {
+        Some(ref raw) => SaveConfig::parse(&save_mode, raw)?,
+        None => SaveConfig::default_for(&save_mode)?,
+    };
     
     let existing_games = state.db.get_games().map_err(|e| e.to_string())?;
     
@@ -115,16 +158,18 @@ fn add_game(
 
     let id = state
         .db
-        .add_game(&name, &game_folder_path, &save_folder_path, Some(exe_path.as_str()), save_mode.as_deref(), save_config.as_deref())
+        .add_game(&name, &game_folder_path, &save_folder_path, Some(exe_path.as_str()), Some(save_mode.as_str()), Some(config.to_json().as_str()))
         .map_err(|e| e.to_string())?;
     
     state
         .watcher
         .lock()
         .map_err(|e| format!("Failed to lock watcher: {}", e))?
-        .watch(&save_folder_path)
+        .watch(&id, &save_folder_path, config.watch_backend)
         .map_err(|e| e.to_string())?;
 
+    refresh_path_index(&state)?;
+
     Ok(id)
 }
 
@@ -165,6 +210,21 @@ fn update_game(
             return Err(format!("游戏执行文件不存在: {}", path));
         }
     }
+
+    // Validate the config against the (possibly new) save mode whenever either changes
+    let old_backend = SaveConfig::for_game(&current_game)
+        .map(|c| c.watch_backend)
+        .unwrap_or(WatchBackend::Native);
+    let (save_config, new_backend) = if save_mode.is_some() || save_config.is_some() {
+        let mode = save_mode.as_deref().or(current_game.save_mode.as_deref()).unwrap_or("single_file");
+        let config = match save_config.as_deref().or(current_game.save_config.as_deref()) {
+            Some(raw) => SaveConfig::parse(mode, raw)?,
+            None => SaveConfig::default_for(mode)?,
+        };
+        (Some(config.to_json()), config.watch_backend)
+    } else {
+        (None, old_backend)
+    };
     
     // Check for duplicate name (excluding current game)
     if name.is_some() {
@@ -241,25 +301,25 @@ fn update_game(
         save_config.as_deref(),
     ).map_err(|e| e.to_string())?;
     
-    // Update watcher if save_folder_path changed
-    if save_folder_path.is_some() {
+    // Update watcher if save_folder_path or the watch backend changed
+    if save_folder_path.is_some() || new_backend != old_backend {
+        let mut watcher = state.watcher.lock()
+            .map_err(|e| format!("Failed to lock watcher: {}", e))?;
+
         // Unwatch old path
-        if let Some(ref old_path) = current_game.save_folder_path {
-            if let Err(_) = state.watcher.lock()
-                .map_err(|e| format!("Failed to lock watcher: {}", e))?
-                .unwatch(old_path) {
-                // Ignore error if path not watched
-            }
+        if let Err(_) = watcher.unwatch(&game_id) {
+            // Ignore error if path not watched
         }
         
         // Watch new path
-        if let Some(ref new_path) = new_save_folder_path {
-            state.watcher.lock()
-                .map_err(|e| format!("Failed to lock watcher: {}", e))?
-                .watch(new_path)
+        if let Some(new_path) = new_save_folder_path {
+            watcher
+                .watch(&game_id, new_path, new_backend)
                 .map_err(|e| e.to_string())?;
         }
     }
+
+    refresh_path_index(&state)?;
     
     Ok(())
 }
@@ -293,6 +353,32 @@ fn get_snapshots(state: State<AppState>, game_id: String) -> Result<Vec<Snapshot
     state.db.get_snapshots(&game_id).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn create_manual_snapshot(
+    state: State<AppState>,
+    game_id: String,
+    name: Option<String>,
+    note: Option<String>,
+) -> Result<Snapshot, String> {
+    let game = state.db.get_game(&game_id).map_err(|e| e.to_string())?;
+    state
+        .snapshot_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock snapshot_manager: {}", e))?
+        .create_manual_snapshot(&game, name, note)
+        .map_err(|e| e.to_string())
+}
+
+/// MIME type for an image file, chosen from its extension; unknown extensions fall back to PNG
+fn image_mime_type(path: &std::path::Path) -> &'static str {
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("bmp") => "image/bmp",
+        Some("webp") => "image/webp",
+        _ => "image/png",
+    }
+}
+
 #[tauri::command]
 fn load_screenshot_image_base64(image_path: String) -> Result<String, String> {
     use std::fs;
@@ -300,7 +386,7 @@ fn load_screenshot_image_base64(image_path: String) -> Result<String, String> {
 
     let bytes = fs::read(&image_path).map_err(|e| e.to_string())?;
     let b64 = general_purpose::STANDARD.encode(&bytes);
-    Ok(format!("data:image/png;base64,{}", b64))
+    Ok(format!("data:{};base64,{}", image_mime_type(std::path::Path::new(&image_path)), b64))
 }
 
 #[tauri::command]
@@ -320,7 +406,129 @@ fn update_snapshot_name(state: State<AppState>, snapshot_id: String, name: Strin
 
 #[tauri::command]
 fn delete_game(state: State<AppState>, game_id: String, delete_visual_logger: bool) -> Result<(), String> {
-    state.db.delete_game(&game_id, delete_visual_logger).map_err(|e| e.to_string())
+    state.db.delete_game(&game_id, delete_visual_logger).map_err(|e| e.to_string())?;
+
+    if let Ok(mut watcher) = state.watcher.lock() {
+        let _ = watcher.unwatch(&game_id);
+    }
+
+    refresh_path_index(&state)
+}
+
+#[tauri::command]
+fn set_game_paused(
+    state: State<AppState>,
+    game_id: String,
+    paused: bool,
+    reconcile: Option<bool>,
+) -> Result<Vec<Snapshot>, String> {
+    state.db.set_game_paused(&game_id, paused).map_err(|e| e.to_string())?;
+    refresh_path_index(&state)?;
+
+    // Optionally snapshot whatever changed while the game was paused
+    if paused || !reconcile.unwrap_or(false) || state.db.get_watching_paused().map_err(|e| e.to_string())? {
+        return Ok(Vec::new());
+    }
+    let game = state.db.get_game(&game_id).map_err(|e| e.to_string())?;
+    state
+        .snapshot_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock snapshot_manager: {}", e))?
+        .reconcile_game(&game, "resume")
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_watching_paused(state: State<AppState>) -> Result<bool, String> {
+    state.db.get_watching_paused().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_watching_paused(
+    state: State<AppState>,
+    paused: bool,
+    reconcile: Option<bool>,
+) -> Result<Vec<Snapshot>, String> {
+    state.db.set_watching_paused(paused).map_err(|e| e.to_string())?;
+
+    if paused || !reconcile.unwrap_or(false) {
+        return Ok(Vec::new());
+    }
+
+    // Games paused individually stay paused, so they are not reconciled here
+    let games = state.db.get_games().map_err(|e| e.to_string())?;
+    let sm = state
+        .snapshot_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock snapshot_manager: {}", e))?;
+    let mut snapshots = Vec::new();
+    for game in games.iter().filter(|g| !g.paused) {
+        match sm.reconcile_game(game, "resume") {
+            Ok(created) => snapshots.extend(created),
+            Err(e) => sm.report_failure(&game.id, &e.to_string()),
+        }
+    }
+    Ok(snapshots)
+}
+
+#[tauri::command]
+fn get_watch_status(state: State<AppState>) -> Result<Vec<WatchStatus>, String> {
+    let watcher = state.watcher.lock().map_err(|e| format!("Failed to lock watcher: {}", e))?;
+    Ok(watcher.status())
+}
+
+#[tauri::command]
+fn preview_save_config(
+    save_folder_path: String,
+    save_mode: Option<String>,
+    save_config: Option<String>,
+) -> Result<snapshot::SaveConfigPreview, String> {
+    use std::path::Path;
+
+    // Same validation as add_game, so a config that previews fine can also be saved
+    let save_mode = save_mode.unwrap_or_else(|| "single_file".to_string());
+    let config = match save_config {
+        Some(ref raw) => SaveConfig::parse(&save_mode, raw)?,
+        None => SaveConfig::default_for(&save_mode)?,
+    };
+    SnapshotManager::preview_save_config(Path::new(&save_folder_path), &save_mode, &config)
+        .map_err(|e| e.to_string())
+}
+
+/// Recognizes the engine from the folder containing the game's exe and returns its preset
+#[tauri::command]
+fn detect_engine(exe_path: String) -> Option<engine::DetectedEngine> {
+    let game_folder = std::path::Path::new(&exe_path).parent()?;
+    engine::detect_engine(game_folder)
+}
+
+#[tauri::command]
+fn start_observing(state: State<AppState>, game_id: String) -> Result<(), String> {
+    use std::path::Path;
+
+    let game = state.db.get_game(&game_id).map_err(|e| e.to_string())?;
+    let save_root = game.save_folder_path.as_deref().unwrap_or(&game.game_folder_path);
+    if !Path::new(save_root).is_dir() {
+        return Err(format!("存档文件夹不存在: {}", save_root));
+    }
+    state.observer.start(&game_id, Path::new(save_root));
+    Ok(())
+}
+
+#[tauri::command]
+fn get_observe_status(state: State<AppState>, game_id: String) -> Option<observe::ObserveStatus> {
+    state.observer.status(&game_id)
+}
+
+#[tauri::command]
+fn get_observing_games(state: State<AppState>) -> Vec<String> {
+    state.observer.observing_games()
+}
+
+/// Ends observe mode and returns a suggested config; nothing is saved until the user applies it
+#[tauri::command]
+fn stop_observing(state: State<AppState>, game_id: String) -> Result<observe::ConfigSuggestion, String> {
+    state.observer.stop(&game_id)
 }
 
 #[tauri::command]
@@ -346,31 +554,118 @@ fn delete_snapshot(state: State<AppState>, snapshot_id: String) -> Result<(), St
     Ok(())
 }
 
+/// Live saves a restore would delete: only a whole folder-mode snapshot restored in place removes
+/// the saves created after it, so the folder comes back as one consistent unit
+fn stale_files_for(
+    snapshot: &Snapshot,
+    game: &Game,
+    entries: &[restore::RestoreEntry],
+    relative_paths: Option<&[String]>,
+    target_dir: Option<&str>,
+) -> Vec<restore::StaleFile> {
+    if target_dir.is_some() || relative_paths.is_some_and(|p| !p.is_empty()) {
+        return Vec::new();
+    }
+    restore::stale_files(snapshot, entries, &SnapshotManager::current_save_files(game))
+}
+
 #[tauri::command]
-fn restore_snapshot(state: State<AppState>, snapshot_id: String) -> Result<(), String> {
-    use std::fs;
+fn restore_snapshot(
+    state: State<AppState>,
+    snapshot_id: String,
+    force: Option<bool>,
+    relative_paths: Option<Vec<String>>,
+    target_dir: Option<String>,
+) -> Result<restore::RestoreOutcome, String> {
+    use std::path::Path;
     
     let snapshot = state.db.get_snapshot(&snapshot_id).map_err(|e| e.to_string())?;
+    let game = state.db.get_game(&snapshot.game_id).map_err(|e| e.to_string())?;
+    let target_dir = target_dir.filter(|d| !d.trim().is_empty());
     
-    let backup_path = std::path::Path::new(&snapshot.backup_save_path);
-    let original_path = std::path::Path::new(&snapshot.original_save_path);
+    let entries = restore::read_restore_entries(&snapshot).map_err(|e| e.to_string())?;
+    let entries = restore::select_entries(entries, relative_paths.as_deref(), target_dir.as_deref().map(Path::new))
+        .map_err(|e| e.to_string())?;
     
-    if !backup_path.exists() {
-        return Err(format!("备份文件不存在: {}", snapshot.backup_save_path));
+    // Extracting into another directory leaves the live saves alone
+    if target_dir.is_some() {
+        if let Some(existing) = entries.iter().find(|e| e.target_path.exists()) {
+            return Err(format!("目标目录中已存在文件: {}", existing.target_path.to_string_lossy()));
+        }
+        let written_paths = restore::written_paths(&entries, &[]);
+        state.write_suppressor.suppress(written_paths.clone(), RESTORE_SUPPRESS_DURATION);
+        let result = restore::restore_entries(&entries, &[], &[]).map_err(|e| e.to_string());
+        state.write_suppressor.suppress(written_paths, RESTORE_SUPPRESS_DURATION);
+        result?;
+        return Ok(restore::RestoreOutcome {
+            restored: true,
+            pre_restore_snapshot_id: None,
+            warnings: Vec::new(),
+        });
     }
     
-    if backup_path.is_dir() {
-        return Err("备份路径是目录，无法恢复。请确保备份路径是文件。".to_string());
+    // The game may overwrite the restored save on exit or keep stale state in memory
+    let mut warnings = Vec::new();
+    if game.exe_path.as_deref().map(screenshot::is_exe_running).unwrap_or(false) {
+        warnings.push(restore::RestoreWarning {
+            code: "game_running".to_string(),
+            message: format!("游戏 \"{}\" 正在运行，恢复的存档可能会被游戏覆盖，建议先关闭游戏", game.name),
+        });
+        if !force.unwrap_or(false) {
+            return Ok(restore::RestoreOutcome {
+                restored: false,
+                pre_restore_snapshot_id: None,
+                warnings,
+            });
+        }
     }
     
-    if let Some(parent) = original_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("无法创建目标目录: {}", e))?;
-    }
+    let stale = stale_files_for(&snapshot, &game, &entries, relative_paths.as_deref(), None);
+    
+    // Keep the current saves, including the ones about to be deleted, so a failed or mistaken restore can be undone
+    let pre_restore = state
+        .snapshot_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock snapshot_manager: {}", e))?
+        .create_pre_restore_snapshot(&game, &snapshot, &entries, &stale)
+        .map_err(|e| format!("创建恢复前快照失败: {}", e))?;
     
-    fs::copy(backup_path, original_path)
-        .map_err(|e| format!("无法复制备份文件到原始路径: {}", e))?;
+    let rollback = match pre_restore {
+        Some(ref pre) => restore::read_restore_entries(pre).map_err(|e| e.to_string())?,
+        None => Vec::new(),
+    };
     
-    Ok(())
+    // Our own writes must not be picked up by the watcher as new saves
+    let written_paths = restore::written_paths(&entries, &stale);
+    state.write_suppressor.suppress(written_paths.clone(), RESTORE_SUPPRESS_DURATION);
+    let result = restore::restore_entries(&entries, &stale, &rollback).map_err(|e| e.to_string());
+    state.write_suppressor.suppress(written_paths, RESTORE_SUPPRESS_DURATION);
+    result?;
+    
+    Ok(restore::RestoreOutcome {
+        restored: true,
+        pre_restore_snapshot_id: pre_restore.map(|s| s.id),
+        warnings,
+    })
+}
+
+#[tauri::command]
+fn preview_restore(
+    state: State<AppState>,
+    snapshot_id: String,
+    relative_paths: Option<Vec<String>>,
+    target_dir: Option<String>,
+) -> Result<Vec<restore::RestorePreviewItem>, String> {
+    use std::path::Path;
+    
+    let snapshot = state.db.get_snapshot(&snapshot_id).map_err(|e| e.to_string())?;
+    let game = state.db.get_game(&snapshot.game_id).map_err(|e| e.to_string())?;
+    let target_dir = target_dir.filter(|d| !d.trim().is_empty());
+    let entries = restore::read_restore_entries(&snapshot).map_err(|e| e.to_string())?;
+    let entries = restore::select_entries(entries, relative_paths.as_deref(), target_dir.as_deref().map(Path::new))
+        .map_err(|e| e.to_string())?;
+    let stale = stale_files_for(&snapshot, &game, &entries, relative_paths.as_deref(), target_dir.as_deref());
+    restore::preview_entries(&entries, &stale).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -737,53 +1032,121 @@ pub fn run() {
                         .save_folder_path
                         .as_deref()
                         .unwrap_or(&game.game_folder_path);
-                    if let Err(_e) = save_watcher.watch(watch_path) {
-                        // Failed to watch game folder
+                    let config = SaveConfig::for_game(&game);
+                    let backend = config.as_ref().map(|c| c.watch_backend).unwrap_or(WatchBackend::Native);
+                    if let Err(_e) = save_watcher.watch(&game.id, watch_path, backend) {
+                        // Failed to watch game folder; the error is reported by get_watch_status
+                    }
+                    // Games with an invalid config take no snapshots until it is fixed
+                    if let Err(e) = config {
+                        save_watcher.record_game_error(&game.id, format!("存档配置无效: {}", e));
                     }
                 }
             }
 
             let watcher_arc = Arc::new(Mutex::new(save_watcher));
+            let write_suppressor = WriteSuppressor::new();
+            let write_suppressor_clone = write_suppressor.clone();
+            let observer = SaveObserver::new();
+            let observer_clone = observer.clone();
+
+            // Saves written while the app was closed never produce watcher events; compare them
+            // against the latest snapshots once at startup
+            let sm_startup = snapshot_manager.clone();
+            let startup_db = Database::new(&handle);
+            std::thread::spawn(move || {
+                if startup_db.get_watching_paused().unwrap_or(false) {
+                    return;
+                }
+                let games = match startup_db.get_games() {
+                    Ok(games) => games,
+                    Err(_) => return,
+                };
+                for game in games.into_iter().filter(|g| !g.paused) {
+                    let result = match sm_startup.lock() {
+                        Ok(sm) => sm.reconcile_game(&game, "startup").map(|_| ()).map_err(|e| e.to_string()),
+                        Err(e) => Err(format!("Failed to lock snapshot_manager: {}", e)),
+                    };
+                    if let Err(e) = result {
+                        if let Ok(sm) = sm_startup.lock() {
+                            sm.report_failure(&game.id, &e);
+                        }
+                    }
+                }
+            });
 
             let sm_clone = snapshot_manager.clone();
-            let last_snapshot_time = Arc::new(Mutex::new(std::time::Instant::now()));
-            let last_snapshot_time_clone = last_snapshot_time.clone();
+            let watcher_clone = watcher_arc.clone();
             
             std::thread::spawn(move || {
-                let debounce_duration = std::time::Duration::from_secs(2);
-
-                for res in rx {
-                    match res {
-                        Ok(event) => {
-                             match event.kind {
-                                notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
-                                    let should_process = {
-                                        match last_snapshot_time_clone.lock() {
-                                            Ok(last_time) => last_time.elapsed() >= debounce_duration,
-                                            Err(_) => continue,
+                use std::sync::mpsc::RecvTimeoutError;
+                
+                // Engines often write several slot files in one burst; wait until a game's saves
+                // have been quiet before snapshotting everything that changed
+                let mut batcher = EventBatcher::new(
+                    std::time::Duration::from_secs(2),
+                    std::time::Duration::from_secs(10),
+                );
+                let idle_timeout = std::time::Duration::from_secs(3600);
+
+                loop {
+                    match rx.recv_timeout(batcher.next_timeout().unwrap_or(idle_timeout)) {
+                        Ok(Ok(event)) => {
+                            if let Ok(mut watcher) = watcher_clone.lock() {
+                                for path in &event.paths {
+                                    watcher.record_event(path);
+                                }
+                            }
+                            match event.kind {
+                                // Remove (and the old name of a rename) is kept so the batch can record a tombstone
+                                notify::EventKind::Create(_) | notify::EventKind::Modify(_) | notify::EventKind::Remove(_) => {
+                                    for path in event.paths {
+                                        if write_suppressor_clone.is_suppressed(&path) {
+                                            continue;
                                         }
-                                    };
-                                    
-                                    if !should_process {
-                                        continue;
-                                    }
-                                    
-                                    if let Some(path) = event.paths.first() {
-                                        match sm_clone.lock() {
+                                        let game_id = match sm_clone.lock() {
                                             Ok(sm) => {
-                                                match sm.process_save_event(path, last_snapshot_time_clone.clone()) {
-                                                    Ok(_) => {},
-                                                    Err(_) => {},
+                                                // Games in observe mode only have their writes recorded
+                                                if sm.game_for_path(&path).is_some_and(|id| observer_clone.record(&id, &path)) {
+                                                    continue;
                                                 }
+                                                sm.match_save_path(&path).ok().flatten()
                                             },
-                                            Err(_) => {},
+                                            Err(_) => None,
+                                        };
+                                        if let Some(game_id) = game_id {
+                                            batcher.add(game_id, path);
                                         }
                                     }
                                 },
                                 _ => {}
                             }
                         },
-                        Err(_) => {},
+                        Ok(Err(e)) => {
+                            if let Ok(mut watcher) = watcher_clone.lock() {
+                                watcher.record_error(&e);
+                            }
+                        },
+                        Err(RecvTimeoutError::Timeout) => {},
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                    
+                    for (game_id, paths) in batcher.take_ready() {
+                        // Each batch waits on its own thread, so a game that keeps its save open does not
+                        // hold up the watcher loop or other games' snapshots
+                        let sm_batch = sm_clone.clone();
+                        std::thread::spawn(move || {
+                            // Wait for the engine to finish writing without holding the snapshot manager
+                            let result = snapshot::wait_for_stable(&paths).and_then(|_| match sm_batch.lock() {
+                                Ok(sm) => sm.process_save_batch(&game_id, &paths).map(|_| ()).map_err(|e| e.to_string()),
+                                Err(e) => Err(format!("Failed to lock snapshot_manager: {}", e)),
+                            });
+                            if let Err(e) = result {
+                                if let Ok(sm) = sm_batch.lock() {
+                                    sm.report_failure(&game_id, &e);
+                                }
+                            }
+                        });
                     }
                 }
             });
@@ -794,15 +1157,22 @@ pub fn run() {
                 let hotkey = HotKey::new(None, Code::F11);
                 manager.register(hotkey.clone()).map_err(|e| format!("Failed to register F11 hotkey: {}", e))?;
                 
+                // Shift+F11 takes a manual snapshot of the running game's saves
+                let snapshot_hotkey = HotKey::new(Some(Modifiers::SHIFT), Code::F11);
+                manager.register(snapshot_hotkey).map_err(|e| format!("Failed to register Shift+F11 hotkey: {}", e))?;
+                
                 let hotkey_id = hotkey.id();
+                let snapshot_hotkey_id = snapshot_hotkey.id();
+                let snapshot_manager_for_hotkey = snapshot_manager.clone();
                 let screenshot_manager_for_hotkey = screenshot_manager.clone();
                 let app_handle_for_hotkey = handle.clone();
                 let last_screenshot_time = Arc::new(Mutex::new(std::time::Instant::now()));
+                let mut last_manual_snapshot_time: Option<std::time::Instant> = None;
                 let is_capturing = Arc::new(Mutex::new(false));
                 let debounce_duration = std::time::Duration::from_millis(2000);
                 
                 std::thread::spawn(move || {
-                    use global_hotkey::GlobalHotKeyEvent;
+                    use global_hotkey::{GlobalHotKeyEvent, HotKeyState};
                     
                     let receiver = GlobalHotKeyEvent::receiver();
                     
@@ -879,6 +1249,25 @@ pub fn run() {
                                             },
                                         }
                                     }
+                                } else if event.id == snapshot_hotkey_id && event.state == HotKeyState::Pressed {
+                                    // Holding the key repeats the event
+                                    if last_manual_snapshot_time.map(|t| t.elapsed() < debounce_duration).unwrap_or(false) {
+                                        continue;
+                                    }
+                                    last_manual_snapshot_time = Some(std::time::Instant::now());
+                                    
+                                    // The snapshot manager emits "snapshot-created" itself; failures are reported
+                                    // the same way as the watcher's, with no game id when nothing is running
+                                    if let Ok(sm) = snapshot_manager_for_hotkey.lock() {
+                                        match sm.find_running_game() {
+                                            Ok(game) => {
+                                                if let Err(e) = sm.create_manual_snapshot(&game, None, None) {
+                                                    sm.report_failure(&game.id, &e.to_string());
+                                                }
+                                            },
+                                            Err(e) => sm.report_failure("", &e.to_string()),
+                                        }
+                                    }
                                 }
                             },
                             Err(_) => break,
@@ -893,6 +1282,8 @@ pub fn run() {
             app.manage(AppState {
                 db,
                 watcher: watcher_arc,
+                write_suppressor,
+                observer,
                 snapshot_manager,
                 screenshot_manager,
                 hotkey_manager,
@@ -902,6 +1293,8 @@ pub fn run() {
             app.manage(AppState {
                 db,
                 watcher: watcher_arc,
+                write_suppressor,
+                observer,
                 snapshot_manager,
                 screenshot_manager,
             });
@@ -914,8 +1307,20 @@ pub fn run() {
             get_games,
             get_game_stats,
             get_snapshots,
+            create_manual_snapshot,
             restore_snapshot,
+            preview_restore,
             delete_game,
+            get_watch_status,
+            preview_save_config,
+            detect_engine,
+            start_observing,
+            get_observe_status,
+            get_observing_games,
+            stop_observing,
+            set_game_paused,
+            get_watching_paused,
+            set_watching_paused,
             delete_snapshot,
             load_snapshot_image_base64,
             update_snapshot_note,
@@ -0,0 +1,363 @@
+use crate::path_index::canonical_path;
+use crate::save_config::SaveConfig;
+use crate::snapshot::{file_extension, is_image};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 观察的最长时间，超时后不再拦截写入，游戏恢复自动快照；已记录的文件仍可用于生成建议
+const OBSERVE_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// 临时文件的扩展名，原子写入或日志产生的文件不是存档
+const TEMP_EXTENSIONS: &[&str] = &["tmp", "temp", "bak", "old", "log", "lock", "swp"];
+
+/// 常见引擎的系统存档（全局设置、已读记录等），恢复时覆盖它们会影响所有存档位
+const SYSTEM_FILE_NAMES: &[&str] = &[
+    "datasc.ksd",
+    "datasu.ksd",
+    "persistent",
+    "config.rpgsave",
+    "global.rpgsave",
+    "config.rmmzsave",
+    "global.rmmzsave",
+];
+
+/// 文件名中出现这些词且没有编号时视为系统文件
+const SYSTEM_KEYWORDS: &[&str] = &["system", "config", "global", "persistent", "option", "setting", "common"];
+
+/// 观察期间写入过的单个文件
+#[derive(Debug, Clone, Serialize)]
+pub struct ObservedFile {
+    /// 相对于存档文件夹的路径（使用 `/` 分隔）
+    pub relative_path: String,
+    pub writes: u32,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// 推测的用途：slot, system, temp
+    pub role: String,
+}
+
+/// 正在进行的观察
+#[derive(Debug, Serialize)]
+pub struct ObserveStatus {
+    pub game_id: String,
+    pub started_at: String,
+    pub expires_at: String,
+    /// 已超过最长观察时间，写入不再被记录
+    pub expired: bool,
+    pub files: Vec<ObservedFile>,
+}
+
+/// 结束观察后给出的存档配置建议，save_config 已通过校验
+#[derive(Debug, Serialize)]
+pub struct ConfigSuggestion {
+    pub save_mode: String,
+    pub save_config: String,
+    pub files: Vec<ObservedFile>,
+    /// 推断过程的说明，供用户判断建议是否可信
+    pub notes: Vec<String>,
+}
+
+struct ObserveSession {
+    save_root: PathBuf,
+    started_at: DateTime<Utc>,
+    started: Instant,
+    files: HashMap<String, ObservedFile>,
+}
+
+impl ObserveSession {
+    fn expired(&self) -> bool {
+        self.started.elapsed() >= OBSERVE_MAX_DURATION
+    }
+}
+
+/// 观察模式：记录游戏运行时写入了存档文件夹中的哪些文件，期间不创建快照
+#[derive(Clone, Default)]
+pub struct SaveObserver {
+    sessions: Arc<Mutex<HashMap<String, ObserveSession>>>,
+}
+
+impl SaveObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始观察；已在观察时重新开始
+    pub fn start(&self, game_id: &str, save_root: &Path) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(game_id.to_string(), ObserveSession {
+                save_root: canonical_path(save_root),
+                started_at: Utc::now(),
+                started: Instant::now(),
+                files: HashMap::new(),
+            });
+        }
+    }
+
+    /// `game_id` 为路径所属的游戏（嵌套目录已按最具体的游戏区分）。
+    /// 该游戏正在观察且路径位于其存档文件夹中时记录下来并返回 true，调用方不应再为其创建快照
+    pub fn record(&self, game_id: &str, path: &Path) -> bool {
+        let mut sessions = match self.sessions.lock() {
+            Ok(sessions) => sessions,
+            Err(_) => return false,
+        };
+        let session = match sessions.get_mut(game_id) {
+            Some(session) if !session.expired() => session,
+            _ => return false,
+        };
+
+        let canonical = canonical_path(path);
+        if !canonical.starts_with(&session.save_root) {
+            return false;
+        }
+
+        // 删除事件和目录只用于吞掉事件，不计入写入
+        if !canonical.is_file() || canonical.components().any(|c| c.as_os_str() == "visual-logger") {
+            return true;
+        }
+        let relative_path = match canonical.strip_prefix(&session.save_root) {
+            Ok(rel) => rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => return true,
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let file = session.files.entry(relative_path.clone()).or_insert_with(|| ObservedFile {
+            relative_path,
+            writes: 0,
+            first_seen: now.clone(),
+            last_seen: now.clone(),
+            role: String::new(),
+        });
+        file.writes += 1;
+        file.last_seen = now;
+        true
+    }
+
+    pub fn status(&self, game_id: &str) -> Option<ObserveStatus> {
+        let sessions = self.sessions.lock().ok()?;
+        let session = sessions.get(game_id)?;
+        let mut files: Vec<ObservedFile> = session.files.values().cloned().collect();
+        files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        let expires_at = session.started_at + chrono::Duration::from_std(OBSERVE_MAX_DURATION).ok()?;
+        Some(ObserveStatus {
+            game_id: game_id.to_string(),
+            started_at: session.started_at.to_rfc3339(),
+            expires_at: expires_at.to_rfc3339(),
+            expired: session.expired(),
+            files,
+        })
+    }
+
+    /// 正在观察（未超时）的游戏
+    pub fn observing_games(&self) -> Vec<String> {
+        match self.sessions.lock() {
+            Ok(sessions) => sessions
+                .iter()
+                .filter(|(_, session)| !session.expired())
+                .map(|(game_id, _)| game_id.clone())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// 结束观察并根据观察到的写入推断存档配置
+    pub fn stop(&self, game_id: &str) -> Result<ConfigSuggestion, String> {
+        let session = self
+            .sessions
+            .lock()
+            .map_err(|e| format!("Failed to lock observer: {}", e))?
+            .remove(game_id)
+            .ok_or("该游戏没有正在进行的观察")?;
+        suggest(&session)
+    }
+}
+
+/// 第一个 `.` 之前的部分，扩展名中的数字（如 rvdata2）不算编号
+fn stem_len(name: &str) -> usize {
+    name.find('.').unwrap_or(name.len())
+}
+
+/// 主文件名中是否带有存档位编号
+fn has_number(name: &str) -> bool {
+    name[..stem_len(name)].contains(|c: char| c.is_ascii_digit())
+}
+
+fn file_name(relative_path: &str) -> &str {
+    relative_path.rsplit('/').next().unwrap_or(relative_path)
+}
+
+fn parent_dir(relative_path: &str) -> &str {
+    relative_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+fn is_temp(save_root: &Path, file: &ObservedFile) -> bool {
+    let name = file_name(&file.relative_path).to_lowercase();
+    // 写入后又消失的文件多半是原子保存时的中间文件
+    !save_root.join(&file.relative_path).is_file()
+        || name.ends_with('~')
+        || name.starts_with("~$")
+        || file_extension(Path::new(&name)).map(|ext| TEMP_EXTENSIONS.contains(&ext.as_str())).unwrap_or(false)
+}
+
+fn is_system(name: &str, has_numbered: bool) -> bool {
+    let lower = name.to_lowercase();
+    if SYSTEM_FILE_NAMES.contains(&lower.as_str()) {
+        return true;
+    }
+    if has_number(&lower) {
+        return false;
+    }
+    // 与编号存档位一起出现的无编号文件通常是全局数据
+    has_numbered || SYSTEM_KEYWORDS.iter().any(|k| lower.contains(k))
+}
+
+/// 把文件名中的编号替换为 `*`，得到存档位的通配符
+fn name_glob(name: &str) -> String {
+    let (stem, rest) = name.split_at(stem_len(name));
+    let mut glob = String::new();
+    let mut in_digits = false;
+    for c in stem.chars() {
+        if c.is_ascii_digit() {
+            if !in_digits {
+                glob.push('*');
+            }
+            in_digits = true;
+        } else {
+            glob.push(c);
+            in_digits = false;
+        }
+    }
+    glob.push_str(rest);
+    glob
+}
+
+fn with_dir(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn suggest(session: &ObserveSession) -> Result<ConfigSuggestion, String> {
+    let mut files: Vec<ObservedFile> = session.files.values().cloned().collect();
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    let has_numbered = files.iter().any(|f| {
+        !is_temp(&session.save_root, f)
+            && !is_image(Path::new(file_name(&f.relative_path)))
+            && has_number(file_name(&f.relative_path))
+    });
+    for file in files.iter_mut() {
+        let name = file_name(&file.relative_path);
+        file.role = if is_temp(&session.save_root, file) {
+            "temp"
+        } else if is_system(name, has_numbered) && !is_image(Path::new(name)) {
+            "system"
+        } else {
+            "slot"
+        }
+        .to_string();
+    }
+
+    let slots: Vec<&ObservedFile> = files.iter().filter(|f| f.role == "slot").collect();
+    if slots.is_empty() {
+        return Err("观察期间没有检测到存档写入，请在游戏中存档后再结束观察".to_string());
+    }
+
+    let mut notes = vec![format!("观察到 {} 个文件被写入，其中 {} 个推测为存档位文件", files.len(), slots.len())];
+
+    let mut include_globs = BTreeSet::new();
+    let mut exclude_globs = BTreeSet::new();
+    let mut slot_extensions = BTreeSet::new();
+    for slot in &slots {
+        let name = file_name(&slot.relative_path);
+        include_globs.insert(with_dir(parent_dir(&slot.relative_path), &name_glob(name)));
+        if let Some(ext) = file_extension(Path::new(name)).filter(|_| !is_image(Path::new(name))) {
+            slot_extensions.insert(ext);
+        }
+    }
+    for file in files.iter().filter(|f| f.role == "system") {
+        exclude_globs.insert(file.relative_path.clone());
+        notes.push(format!("{} 没有存档位编号，视为系统文件并排除", file.relative_path));
+    }
+    for file in files.iter().filter(|f| f.role == "temp") {
+        let name = file_name(&file.relative_path);
+        match file_extension(Path::new(name)).filter(|ext| TEMP_EXTENSIONS.contains(&ext.as_str())) {
+            Some(ext) => exclude_globs.insert(format!("*.{}", ext)),
+            None => exclude_globs.insert(file.relative_path.clone()),
+        };
+    }
+
+    let slot_dirs: BTreeSet<&str> = slots.iter().map(|s| parent_dir(&s.relative_path)).collect();
+    let slot_names: Vec<&str> = slots.iter().map(|s| file_name(&s.relative_path)).collect();
+
+    // 同名不同扩展名的文件（如 save_001.dat + save_001.png）属于同一存档位，与 group_by_prefix 的分组方式一致
+    let mut by_stem: HashMap<String, usize> = HashMap::new();
+    for name in &slot_names {
+        *by_stem.entry(name[..stem_len(name)].to_lowercase()).or_default() += 1;
+    }
+    let has_companions = by_stem.values().any(|count| *count > 1);
+    let missing_extension = slot_names.iter().any(|name| file_extension(Path::new(name)).is_none());
+
+    let (save_mode, mode_config) = if slot_dirs.len() > 1 {
+        let top_dirs: BTreeSet<&str> = slot_dirs.iter().map(|d| d.split('/').next().unwrap_or("")).collect();
+        let folder_name = match top_dirs.iter().next() {
+            Some(dir) if top_dirs.len() == 1 && !dir.is_empty() => Some(dir.to_string()),
+            _ => None,
+        };
+        notes.push(format!("存档写入分布在 {} 个文件夹中，建议整体备份文件夹", slot_dirs.len()));
+        // folder 模式备份整个文件夹，逐个列出存档位会漏掉新的存档位
+        include_globs.clear();
+        ("folder", serde_json::json!({
+            "folder_name": folder_name,
+            "include_extensions": [],
+            "exclude_extensions": [],
+        }))
+    } else if has_companions || missing_extension {
+        if has_companions {
+            notes.push("同一存档位有多个文件（如存档和截图），建议按文件组保存".to_string());
+        }
+        if missing_extension {
+            // 没有扩展名的存档无法按扩展名过滤，只靠 include_globs 选出存档位
+            slot_extensions.clear();
+        }
+        ("file_group", serde_json::json!({
+            "extensions": slot_extensions,
+            "pattern": null,
+            "group_by_prefix": true,
+        }))
+    } else {
+        notes.push(format!(
+            "存档位文件的扩展名: {}",
+            slot_extensions.iter().cloned().collect::<Vec<_>>().join(", ")
+        ));
+        ("single_file", serde_json::json!({ "extensions": slot_extensions }))
+    };
+
+    let mut config = mode_config;
+    if let Some(map) = config.as_object_mut() {
+        if !include_globs.is_empty() {
+            map.insert("include_globs".to_string(), serde_json::json!(include_globs));
+        }
+        if !exclude_globs.is_empty() {
+            map.insert("exclude_globs".to_string(), serde_json::json!(exclude_globs));
+        }
+    }
+    let save_config = SaveConfig::parse(save_mode, &config.to_string())
+        .map_err(|e| format!("无法根据观察结果生成存档配置: {}", e))?;
+
+    Ok(ConfigSuggestion {
+        save_mode: save_mode.to_string(),
+        save_config: save_config.to_json(),
+        files,
+        notes,
+    })
+}
@@ -0,0 +1,77 @@
+use crate::db::Game;
+use std::path::{Path, PathBuf};
+
+/// 规范化路径：解析符号链接、`..` 以及大小写等差异。
+/// 文件已被删除时，规范化其最近的已存在的上级目录再拼回剩余部分
+pub fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+
+    let mut missing = Vec::new();
+    let mut current = path;
+    while let Some(parent) = current.parent() {
+        if let Some(name) = current.file_name() {
+            missing.push(name.to_os_string());
+        }
+        if let Ok(canonical) = parent.canonicalize() {
+            return missing.iter().rev().fold(canonical, |acc, name| acc.join(name));
+        }
+        current = parent;
+    }
+
+    path.to_path_buf()
+}
+
+struct IndexEntry {
+    root: PathBuf,
+    /// 根目录的路径层级数，用于选出最具体的匹配
+    depth: usize,
+    game: Game,
+}
+
+/// 游戏目录的内存索引，用于把 watcher 事件的路径对应到游戏。
+/// 每个游戏以存档目录和游戏目录（规范化后）作为根目录；目录嵌套时选择层级最深的根目录
+#[derive(Default)]
+pub struct PathIndex {
+    entries: Vec<IndexEntry>,
+}
+
+impl PathIndex {
+    pub fn build(games: Vec<Game>) -> Self {
+        let mut entries = Vec::new();
+        for game in games {
+            // 存档目录排在游戏目录之前，两者相同时优先作为存档目录匹配
+            let mut roots = Vec::new();
+            if let Some(ref save_folder) = game.save_folder_path {
+                roots.push(canonical_path(Path::new(save_folder)));
+            }
+            if !game.game_folder_path.is_empty() {
+                roots.push(canonical_path(Path::new(&game.game_folder_path)));
+            }
+            roots.dedup();
+
+            for root in roots {
+                entries.push(IndexEntry {
+                    depth: root.components().count(),
+                    root,
+                    game: game.clone(),
+                });
+            }
+        }
+
+        // 稳定排序，相同层级时保持上面的先后顺序
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.depth));
+
+        Self { entries }
+    }
+
+    /// 找到包含该路径的最具体的游戏
+    pub fn find(&self, path: &Path) -> Option<&Game> {
+        let canonical = canonical_path(path);
+        self.entries
+            .iter()
+            .find(|entry| canonical.starts_with(&entry.root))
+            .map(|entry| &entry.game)
+    }
+}
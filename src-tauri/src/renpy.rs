@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// 读取存档内单个条目的大小上限，防止损坏的存档占用过多内存
+const MAX_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Ren'Py `.save` 存档（zip）中可以直接读取的部分；`log` 是 pickle 数据，不解析
+pub struct RenpySave {
+    /// `extra_info` 中的存档名，为空时取 json 中的 `_save_name`
+    pub save_name: Option<String>,
+    /// `json` 条目，包含 `_save_name`、`_ctime`、`_game_runtime`、`_renpy_version` 等
+    pub json: Option<serde_json::Value>,
+    /// `screenshot.png` 的内容
+    pub screenshot: Option<Vec<u8>>,
+}
+
+pub fn is_renpy_save(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().eq_ignore_ascii_case("save"))
+        .unwrap_or(false)
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Option<Vec<u8>> {
+    let entry = archive.by_name(name).ok()?;
+    if entry.size() > MAX_ENTRY_SIZE {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    entry.take(MAX_ENTRY_SIZE).read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// 解析 Ren'Py 存档；不是 zip 或缺少 Ren'Py 特有条目时返回 None
+pub fn read_renpy_save(path: &Path) -> Option<RenpySave> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    if archive.by_name("log").is_err() {
+        return None;
+    }
+
+    let json: Option<serde_json::Value> = read_entry(&mut archive, "json")
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+    let extra_info = read_entry(&mut archive, "extra_info")
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .filter(|s| !s.is_empty());
+    let save_name = extra_info.or_else(|| {
+        json.as_ref()
+            .and_then(|j| j.get("_save_name"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    });
+
+    Some(RenpySave {
+        save_name,
+        json,
+        screenshot: read_entry(&mut archive, "screenshot.png"),
+    })
+}
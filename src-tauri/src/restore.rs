@@ -0,0 +1,366 @@
+use crate::db::Snapshot;
+use crate::snapshot::modified_string;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 快照目录中由应用自身写入、不属于存档的文件
+const SNAPSHOT_AUX_FILES: &[&str] = &["metadata.json", "note.txt", "context.txt"];
+
+/// 快照中的一个待恢复文件
+pub struct RestoreEntry {
+    /// 相对于存档根目录的路径（使用 `/` 分隔）
+    pub relative_path: String,
+    pub backup_path: PathBuf,
+    pub target_path: PathBuf,
+}
+
+/// 恢复时要删除的当前存档文件
+pub struct StaleFile {
+    /// 相对于存档根目录的路径（使用 `/` 分隔）
+    pub relative_path: String,
+    pub target_path: PathBuf,
+}
+
+/// 恢复时需要提示用户的情况，`code` 供前端判断，`message` 可直接展示
+#[derive(Debug, Serialize)]
+pub struct RestoreWarning {
+    pub code: String,  // game_running
+    pub message: String,
+}
+
+/// restore_snapshot 的结果；`restored` 为 false 时表示因 warnings 中的原因拒绝恢复
+#[derive(Debug, Serialize)]
+pub struct RestoreOutcome {
+    pub restored: bool,
+    pub pre_restore_snapshot_id: Option<String>,
+    pub warnings: Vec<RestoreWarning>,
+}
+
+/// 恢复预览中单个文件的情况
+#[derive(Debug, Serialize)]
+pub struct RestorePreviewItem {
+    pub relative_path: String,
+    pub target_path: String,
+    pub action: String,  // create, overwrite, unchanged, delete
+    /// 要删除的文件在快照中没有副本，为 None
+    pub backup_size: Option<u64>,
+    pub backup_modified: Option<String>,
+    pub current_size: Option<u64>,
+    pub current_modified: Option<String>,
+}
+
+fn read_metadata(snapshot_dir: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(snapshot_dir.join("metadata.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn file_name_string(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 解析快照中的存档文件及其原始位置
+pub fn read_restore_entries(snapshot: &Snapshot) -> Result<Vec<RestoreEntry>, Box<dyn std::error::Error>> {
+    let backup_path = Path::new(&snapshot.backup_save_path);
+    let original_path = Path::new(&snapshot.original_save_path);
+
+    if !backup_path.exists() {
+        return Err(format!("备份文件不存在: {}", snapshot.backup_save_path).into());
+    }
+
+    // 早期版本的快照直接是文件
+    if backup_path.is_file() {
+        return Ok(vec![RestoreEntry {
+            relative_path: file_name_string(original_path),
+            backup_path: backup_path.to_path_buf(),
+            target_path: original_path.to_path_buf(),
+        }]);
+    }
+
+    let metadata = read_metadata(backup_path);
+
+    if let Some(files) = metadata.as_ref().and_then(|m| m.get("files")).and_then(|f| f.as_array()) {
+        let mut entries = Vec::new();
+        for file in files {
+            let backup_file = file.get("backup_file").and_then(|v| v.as_str())
+                .ok_or("metadata.json 中缺少 backup_file 字段")?;
+            let original = file.get("original_path").and_then(|v| v.as_str())
+                .ok_or("metadata.json 中缺少 original_path 字段")?;
+            let relative_path = file.get("relative_path").and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| file_name_string(Path::new(original)));
+            entries.push(RestoreEntry {
+                relative_path,
+                backup_path: backup_path.join(backup_file),
+                target_path: PathBuf::from(original),
+            });
+        }
+        if entries.is_empty() {
+            return Err("快照中没有可恢复的文件".into());
+        }
+        return Ok(entries);
+    }
+
+    // 只记录了 dat_file / dat_path 的单文件快照
+    if let Some(dat_file) = metadata.as_ref().and_then(|m| m.get("dat_file")).and_then(|v| v.as_str()) {
+        let target = metadata.as_ref()
+            .and_then(|m| m.get("dat_path"))
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| original_path.to_path_buf());
+        return Ok(vec![RestoreEntry {
+            relative_path: dat_file.to_string(),
+            backup_path: backup_path.join(dat_file),
+            target_path: target,
+        }]);
+    }
+
+    // 没有 metadata.json 时，只有目录中恰好一个存档文件才能确定恢复目标
+    let candidates: Vec<PathBuf> = fs::read_dir(backup_path)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && !SNAPSHOT_AUX_FILES.contains(&file_name_string(p).as_str()))
+        .collect();
+    match candidates.as_slice() {
+        [only] => Ok(vec![RestoreEntry {
+            relative_path: file_name_string(original_path),
+            backup_path: only.clone(),
+            target_path: original_path.to_path_buf(),
+        }]),
+        [] => Err("快照目录中没有存档文件".into()),
+        _ => Err("快照缺少 metadata.json，无法确定多个存档文件的恢复位置".into()),
+    }
+}
+
+fn temp_path_for(target: &Path) -> PathBuf {
+    target.with_file_name(format!(".{}.vnsm-restore.tmp", file_name_string(target)))
+}
+
+/// 只保留 relative_paths 中选中的文件；target_dir 不为空时改为恢复到该目录下的相对路径
+pub fn select_entries(
+    entries: Vec<RestoreEntry>,
+    relative_paths: Option<&[String]>,
+    target_dir: Option<&Path>,
+) -> Result<Vec<RestoreEntry>, Box<dyn std::error::Error>> {
+    let mut selected = match relative_paths {
+        Some(paths) if !paths.is_empty() => {
+            if let Some(missing) = paths.iter().find(|p| !entries.iter().any(|e| &e.relative_path == *p)) {
+                return Err(format!("快照中不存在文件: {}", missing).into());
+            }
+            entries.into_iter().filter(|e| paths.contains(&e.relative_path)).collect()
+        }
+        _ => entries,
+    };
+
+    if let Some(dir) = target_dir {
+        if !dir.is_dir() {
+            return Err(format!("目标目录不存在: {}", dir.to_string_lossy()).into());
+        }
+        for entry in &mut selected {
+            let relative = Path::new(&entry.relative_path);
+            if !relative.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+                return Err(format!("无效的相对路径: {}", entry.relative_path).into());
+            }
+            entry.target_path = dir.join(relative);
+        }
+    }
+
+    Ok(selected)
+}
+
+/// folder 模式的快照是一个整体：整体恢复时，`current`（当前的存档文件）中快照里没有的文件
+/// 是快照之后新出现的（如新的存档位），需要删除。其他模式的快照不删除任何文件
+pub fn stale_files(snapshot: &Snapshot, entries: &[RestoreEntry], current: &[PathBuf]) -> Vec<StaleFile> {
+    let metadata = match read_metadata(Path::new(&snapshot.backup_save_path)) {
+        Some(m) => m,
+        None => return Vec::new(),
+    };
+    if metadata.get("save_mode").and_then(|v| v.as_str()) != Some("folder") {
+        return Vec::new();
+    }
+    // 恢复前快照和墓碑记录只包含部分文件，没有 source_root
+    let root = match metadata.get("source_root").and_then(|v| v.as_str()) {
+        Some(root) => PathBuf::from(root),
+        None => return Vec::new(),
+    };
+
+    current
+        .iter()
+        .filter(|path| !entries.iter().any(|e| &e.target_path == *path))
+        .filter_map(|path| {
+            let relative = path.strip_prefix(&root).ok()?;
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            Some(StaleFile {
+                relative_path: parts.join("/"),
+                target_path: path.clone(),
+            })
+        })
+        .collect()
+}
+
+/// 恢复过程中会写入或删除的所有路径（目标文件及其临时文件）
+pub fn written_paths(entries: &[RestoreEntry], stale: &[StaleFile]) -> Vec<PathBuf> {
+    entries
+        .iter()
+        .flat_map(|e| [e.target_path.clone(), temp_path_for(&e.target_path)])
+        .chain(stale.iter().map(|f| f.target_path.clone()))
+        .collect()
+}
+
+/// 先写入同目录下的临时文件，再通过重命名原子地替换目标文件
+fn write_atomic(source: &Path, target: &Path) -> std::io::Result<()> {
+    let temp = temp_path_for(target);
+    let result = fs::copy(source, &temp)
+        .and_then(|_| fs::File::open(&temp)?.sync_all())
+        .and_then(|_| fs::rename(&temp, target));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// 将已写入的文件恢复为 rollback 中的版本；原本不存在的文件直接删除
+fn rollback_written(written: &[(&RestoreEntry, bool)], rollback: &[RestoreEntry]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (entry, existed) in written {
+        let result = match rollback.iter().find(|r| r.target_path == entry.target_path) {
+            Some(previous) => write_atomic(&previous.backup_path, &entry.target_path),
+            None if !existed => fs::remove_file(&entry.target_path),
+            None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "没有恢复前的副本")),
+        };
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", entry.target_path.to_string_lossy(), e));
+        }
+    }
+    errors
+}
+
+/// 按 rollback 重新写回已删除的文件
+fn rollback_deleted(deleted: &[&StaleFile], rollback: &[RestoreEntry]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for file in deleted {
+        let result = match rollback.iter().find(|r| r.target_path == file.target_path) {
+            Some(previous) => write_atomic(&previous.backup_path, &file.target_path),
+            None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "没有恢复前的副本")),
+        };
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", file.target_path.to_string_lossy(), e));
+        }
+    }
+    errors
+}
+
+fn rollback_failure(message: String, rollback_errors: Vec<String>) -> Box<dyn std::error::Error> {
+    if rollback_errors.is_empty() {
+        format!("{}\n已回滚到恢复前的状态", message).into()
+    } else {
+        format!("{}\n回滚失败:\n{}", message, rollback_errors.join("\n")).into()
+    }
+}
+
+/// 将快照文件写回原始位置并删除 stale 中的文件；任一文件失败时按 rollback（恢复前快照）回滚已写入和已删除的文件
+pub fn restore_entries(entries: &[RestoreEntry], stale: &[StaleFile], rollback: &[RestoreEntry]) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in entries {
+        if !entry.backup_path.is_file() {
+            return Err(format!("备份文件不存在: {}", entry.backup_path.to_string_lossy()).into());
+        }
+    }
+
+    let mut written = Vec::new();
+    for entry in entries {
+        let existed = entry.target_path.exists();
+        let result = entry.target_path.parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| write_atomic(&entry.backup_path, &entry.target_path));
+
+        if let Err(e) = result {
+            let message = format!("无法恢复文件 {}: {}", entry.target_path.to_string_lossy(), e);
+            return Err(rollback_failure(message, rollback_written(&written, rollback)));
+        }
+        written.push((entry, existed));
+    }
+
+    let mut deleted = Vec::new();
+    for file in stale {
+        match fs::remove_file(&file.target_path) {
+            Ok(()) => deleted.push(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                let message = format!("无法删除文件 {}: {}", file.target_path.to_string_lossy(), e);
+                let mut rollback_errors = rollback_written(&written, rollback);
+                rollback_errors.extend(rollback_deleted(&deleted, rollback));
+                return Err(rollback_failure(message, rollback_errors));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn same_content(a: &Path, b: &Path) -> std::io::Result<bool> {
+    use std::io::Read;
+
+    let mut file_a = std::io::BufReader::new(fs::File::open(a)?);
+    let mut file_b = std::io::BufReader::new(fs::File::open(b)?);
+    let mut buf_a = [0u8; 8192];
+    let mut buf_b = [0u8; 8192];
+    loop {
+        let n = file_a.read(&mut buf_a)?;
+        if n == 0 {
+            return Ok(file_b.read(&mut buf_b[..1])? == 0);
+        }
+        match file_b.read_exact(&mut buf_b[..n]) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        if buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+    }
+}
+
+/// 计算恢复会对每个文件做什么，不修改任何文件
+pub fn preview_entries(entries: &[RestoreEntry], stale: &[StaleFile]) -> Result<Vec<RestorePreviewItem>, Box<dyn std::error::Error>> {
+    let mut items = Vec::new();
+    for entry in entries {
+        let backup_meta = fs::metadata(&entry.backup_path)
+            .map_err(|e| format!("备份文件不存在: {}: {}", entry.backup_path.to_string_lossy(), e))?;
+
+        let current_meta = fs::metadata(&entry.target_path).ok().filter(|m| m.is_file());
+        let action = match current_meta {
+            None => "create",
+            Some(ref meta) if meta.len() == backup_meta.len()
+                && same_content(&entry.backup_path, &entry.target_path)? => "unchanged",
+            Some(_) => "overwrite",
+        };
+
+        items.push(RestorePreviewItem {
+            relative_path: entry.relative_path.clone(),
+            target_path: entry.target_path.to_string_lossy().to_string(),
+            action: action.to_string(),
+            backup_size: Some(backup_meta.len()),
+            backup_modified: modified_string(backup_meta.modified().ok()),
+            current_size: current_meta.as_ref().map(|m| m.len()),
+            current_modified: current_meta.as_ref().and_then(|m| modified_string(m.modified().ok())),
+        });
+    }
+    for file in stale {
+        let current_meta = fs::metadata(&file.target_path).ok();
+        items.push(RestorePreviewItem {
+            relative_path: file.relative_path.clone(),
+            target_path: file.target_path.to_string_lossy().to_string(),
+            action: "delete".to_string(),
+            backup_size: None,
+            backup_modified: None,
+            current_size: current_meta.as_ref().map(|m| m.len()),
+            current_modified: current_meta.as_ref().and_then(|m| modified_string(m.modified().ok())),
+        });
+    }
+    Ok(items)
+}
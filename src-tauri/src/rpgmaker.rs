@@ -0,0 +1,431 @@
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const LZ_BASE64_KEYS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+
+/// 每秒帧数，`_framesOnSave` 以帧为单位
+const FRAMES_PER_SECOND: u64 = 60;
+
+/// 摘要中列出的开关、变量变化的上限
+const MAX_LISTED_CHANGES: usize = 200;
+
+/// 队伍中的角色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartyMember {
+    pub actor_id: i64,
+    pub name: String,
+    pub level: Option<i64>,
+}
+
+/// 从 RPG Maker 存档中提取的摘要；开关和变量只保存非默认值，用于与下一次快照比较
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveSummary {
+    pub map_id: Option<i64>,
+    pub map_name: Option<String>,
+    pub playtime_seconds: Option<u64>,
+    pub gold: Option<i64>,
+    pub save_count: Option<i64>,
+    pub party: Vec<PartyMember>,
+    /// 处于打开状态的开关编号
+    pub switches_on: Vec<usize>,
+    /// 值不为 0 / null 的变量，键为变量编号
+    pub variables: BTreeMap<usize, serde_json::Value>,
+}
+
+/// 与上一次快照相比发生变化的开关或变量
+#[derive(Debug, Serialize)]
+pub struct ValueChange {
+    pub id: usize,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SaveChanges {
+    pub switches: Vec<ValueChange>,
+    pub variables: Vec<ValueChange>,
+    /// 变化过多时只列出前 MAX_LISTED_CHANGES 项
+    pub truncated: bool,
+}
+
+pub fn is_rpgmaker_save(path: &Path) -> bool {
+    path.extension()
+        .map(|e| {
+            let ext = e.to_string_lossy().to_lowercase();
+            ext == "rpgsave" || ext == "rmmzsave"
+        })
+        .unwrap_or(false)
+}
+
+/// LZString.decompressFromBase64（MV 存档的格式）
+fn lz_decompress_from_base64(input: &str) -> Option<String> {
+    let values: Vec<u32> = input
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| LZ_BASE64_KEYS.iter().position(|&k| k == b).map(|p| p as u32))
+        .collect::<Option<_>>()?;
+    if values.is_empty() {
+        return None;
+    }
+    lz_decompress(&values, 32)
+}
+
+struct BitReader<'a> {
+    data: &'a [u32],
+    reset_value: u32,
+    value: u32,
+    position: u32,
+    index: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u32) -> u32 {
+        let mut result = 0;
+        for i in 0..bits {
+            let bit = self.value & self.position;
+            self.position >>= 1;
+            if self.position == 0 {
+                self.position = self.reset_value;
+                self.value = self.data.get(self.index).copied().unwrap_or(0);
+                self.index += 1;
+            }
+            if bit > 0 {
+                result |= 1 << i;
+            }
+        }
+        result
+    }
+}
+
+fn lz_decompress(data: &[u32], reset_value: u32) -> Option<String> {
+    let mut reader = BitReader {
+        data,
+        reset_value,
+        value: data[0],
+        position: reset_value,
+        index: 1,
+    };
+
+    // 0、1、2 是控制码，字典从 3 开始
+    let mut dictionary: Vec<Vec<u16>> = vec![Vec::new(), Vec::new(), Vec::new()];
+    let mut enlarge_in: u32 = 4;
+    let mut num_bits: u32 = 3;
+
+    let first = match reader.read(2) {
+        0 => reader.read(8) as u16,
+        1 => reader.read(16) as u16,
+        _ => return Some(String::new()),
+    };
+    dictionary.push(vec![first]);
+    let mut word = vec![first];
+    let mut result = vec![first];
+
+    loop {
+        if reader.index > data.len() {
+            return None;
+        }
+
+        let mut code = reader.read(num_bits) as usize;
+        match code {
+            0 | 1 => {
+                let c = reader.read(if code == 0 { 8 } else { 16 }) as u16;
+                dictionary.push(vec![c]);
+                code = dictionary.len() - 1;
+                enlarge_in -= 1;
+            }
+            2 => return String::from_utf16(&result).ok(),
+            _ => {}
+        }
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+
+        let entry = if code < dictionary.len() {
+            dictionary[code].clone()
+        } else if code == dictionary.len() {
+            let mut entry = word.clone();
+            entry.push(word[0]);
+            entry
+        } else {
+            return None;
+        };
+        result.extend_from_slice(&entry);
+
+        let mut new_word = word;
+        new_word.push(entry[0]);
+        dictionary.push(new_word);
+        enlarge_in -= 1;
+        word = entry;
+
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+    }
+}
+
+fn inflate(bytes: &[u8]) -> Option<String> {
+    let mut text = String::new();
+    ZlibDecoder::new(bytes).read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+/// 解码存档内容为 JSON。MV 为 LZString base64；MZ 为 zlib，
+/// 由于 MZ 以字符串写入文件，压缩数据的每个字节可能被写成了一个 UTF-8 字符。
+/// 两种格式都没有校验，截断的存档可能解码出一段前缀，因此只接受完整的 JSON
+fn decode_bytes(bytes: &[u8]) -> Option<serde_json::Value> {
+    let parse = |decoded: String| serde_json::from_str(&decoded).ok();
+    let text = std::str::from_utf8(bytes).ok();
+    text.and_then(|t| lz_decompress_from_base64(t.trim()))
+        .and_then(parse)
+        .or_else(|| inflate(bytes).and_then(parse))
+        .or_else(|| {
+            let latin1: Option<Vec<u8>> = text?.chars().map(|c| u8::try_from(c as u32).ok()).collect();
+            inflate(&latin1?).and_then(parse)
+        })
+}
+
+pub fn decode_save(path: &Path) -> Result<serde_json::Value, String> {
+    let bytes = fs::read(path).map_err(|e| format!("读取存档失败: {}", e))?;
+    decode_bytes(&bytes).ok_or_else(|| "无法解码存档：不是 LZString 或 zlib 压缩的 JSON，或存档不完整".to_string())
+}
+
+/// MV 的 JsonEx 会把数组包装成 `{"@c": n, "@a": [...]}`
+fn as_array(value: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+    value.as_array().or_else(|| value.get("@a")?.as_array())
+}
+
+/// data/MapInfos.json 中的地图名；MV 的数据在 www/ 下
+fn map_name(game_folder: &Path, map_id: i64) -> Option<String> {
+    let content = ["www/data/MapInfos.json", "data/MapInfos.json"]
+        .iter()
+        .find_map(|rel| fs::read_to_string(game_folder.join(rel)).ok())?;
+    let infos: serde_json::Value = serde_json::from_str(&content).ok()?;
+    infos
+        .as_array()?
+        .iter()
+        .find(|info| info.get("id").and_then(|v| v.as_i64()) == Some(map_id))?
+        .get("name")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+pub fn summarize(save: &serde_json::Value, game_folder: &Path) -> SaveSummary {
+    let map_id = save.pointer("/map/_mapId").and_then(|v| v.as_i64());
+
+    let actors = save.pointer("/actors/_data").and_then(as_array);
+    let party = save
+        .pointer("/party/_actors")
+        .and_then(as_array)
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| id.as_i64())
+                .map(|actor_id| {
+                    let actor = actors.and_then(|a| a.get(actor_id as usize));
+                    PartyMember {
+                        actor_id,
+                        name: actor
+                            .and_then(|a| a.get("_name"))
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        level: actor.and_then(|a| a.get("_level")).and_then(|v| v.as_i64()),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let switches_on = save
+        .pointer("/switches/_data")
+        .and_then(as_array)
+        .map(|values| {
+            values
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.as_bool() == Some(true))
+                .map(|(id, _)| id)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let variables = save
+        .pointer("/variables/_data")
+        .and_then(as_array)
+        .map(|values| {
+            values
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| !v.is_null() && v.as_f64() != Some(0.0))
+                .map(|(id, v)| (id, v.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    SaveSummary {
+        map_id,
+        map_name: map_id.and_then(|id| map_name(game_folder, id)),
+        playtime_seconds: save
+            .pointer("/system/_framesOnSave")
+            .and_then(|v| v.as_u64())
+            .map(|frames| frames / FRAMES_PER_SECOND),
+        gold: save.pointer("/party/_gold").and_then(|v| v.as_i64()),
+        save_count: save.pointer("/system/_saveCount").and_then(|v| v.as_i64()),
+        party,
+        switches_on,
+        variables,
+    }
+}
+
+/// 比较两次快照之间开关与变量的变化
+pub fn diff(previous: &SaveSummary, current: &SaveSummary) -> SaveChanges {
+    let mut changes = SaveChanges::default();
+
+    let mut switch_ids: Vec<usize> = previous
+        .switches_on
+        .iter()
+        .filter(|id| !current.switches_on.contains(id))
+        .chain(current.switches_on.iter().filter(|id| !previous.switches_on.contains(id)))
+        .copied()
+        .collect();
+    switch_ids.sort_unstable();
+    for id in switch_ids {
+        let on = current.switches_on.contains(&id);
+        changes.switches.push(ValueChange {
+            id,
+            from: serde_json::json!(!on),
+            to: serde_json::json!(on),
+        });
+    }
+
+    let zero = serde_json::json!(0);
+    let mut variable_ids: Vec<usize> = previous.variables.keys().chain(current.variables.keys()).copied().collect();
+    variable_ids.sort_unstable();
+    variable_ids.dedup();
+    for id in variable_ids {
+        let from = previous.variables.get(&id).unwrap_or(&zero);
+        let to = current.variables.get(&id).unwrap_or(&zero);
+        if from != to {
+            changes.variables.push(ValueChange {
+                id,
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+
+    if changes.switches.len() + changes.variables.len() > MAX_LISTED_CHANGES {
+        changes.truncated = true;
+        changes.switches.truncate(MAX_LISTED_CHANGES);
+        changes.variables.truncate(MAX_LISTED_CHANGES - changes.switches.len());
+    }
+    changes
+}
+
+/// 一行可读的摘要，保存到数据库中用于快照列表
+pub fn describe(summary: &SaveSummary, changes: Option<&SaveChanges>) -> String {
+    let mut parts = Vec::new();
+    match (&summary.map_name, summary.map_id) {
+        (Some(name), _) if !name.is_empty() => parts.push(name.clone()),
+        (_, Some(id)) => parts.push(format!("地图 {}", id)),
+        _ => {}
+    }
+    if let Some(seconds) = summary.playtime_seconds {
+        parts.push(format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60));
+    }
+    let members: Vec<String> = summary
+        .party
+        .iter()
+        .map(|m| match m.level {
+            Some(level) => format!("{} Lv{}", m.name, level),
+            None => m.name.clone(),
+        })
+        .collect();
+    if !members.is_empty() {
+        parts.push(members.join("、"));
+    }
+    if let Some(changes) = changes {
+        if !changes.switches.is_empty() || !changes.variables.is_empty() {
+            parts.push(format!(
+                "开关变化 {}{}，变量变化 {}",
+                changes.switches.len(),
+                if changes.truncated { "+" } else { "" },
+                changes.variables.len()
+            ));
+        }
+    }
+    parts.join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// LZString.compressToBase64("Hello, world")
+    const HELLO_WORLD_BASE64: &str = "BIUwNmD2A0AEDukBOYAmQ===";
+
+    const SAVE_JSON: &str = r#"{"party":{"_gold":123,"_actors":[1,2]},"system":{"_framesOnSave":7260},"map":{"_mapId":3}}"#;
+
+    /// LZString.compressToBase64(SAVE_JSON)
+    const SAVE_BASE64: &str = "N4IgDghgTgLgniAXKA+gcwPYBsAmSCMATAMwA0IKEAxjBlAM5IDa+phAugL7n1z0wBTALZJUAMygQhA+gHkAdgGUIANwFIA7IQBsABm4ghEMKIpGwASTyJinTkA=";
+
+    fn save_json() -> serde_json::Value {
+        serde_json::from_str(SAVE_JSON).unwrap()
+    }
+
+    fn zlib(text: &str) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn decodes_lz_string_base64() {
+        assert_eq!(lz_decompress_from_base64(HELLO_WORLD_BASE64).as_deref(), Some("Hello, world"));
+        assert_eq!(decode_bytes(SAVE_BASE64.as_bytes()), Some(save_json()));
+    }
+
+    #[test]
+    fn decodes_zlib_and_mz_utf8_encoded_zlib() {
+        let compressed = zlib(SAVE_JSON);
+        assert_eq!(decode_bytes(&compressed), Some(save_json()));
+
+        // MZ 把压缩数据当作字符串写入，每个字节成为一个字符
+        let as_text: String = compressed.iter().map(|&b| char::from(b)).collect();
+        assert!(as_text.len() > compressed.len());
+        assert_eq!(decode_bytes(as_text.as_bytes()), Some(save_json()));
+    }
+
+    #[test]
+    fn truncated_or_garbage_input_is_rejected() {
+        assert_eq!(decode_bytes(b""), None);
+        assert_eq!(decode_bytes(b"!!not a save!!"), None);
+        assert_eq!(decode_bytes(&[0xff, 0x00, 0x13, 0x37]), None);
+        assert_eq!(lz_decompress_from_base64("AAAA"), None);
+
+        // 截断的 LZString 可能解码出一段前缀，不是完整的 JSON
+        for len in 1..SAVE_BASE64.len() - 4 {
+            assert_eq!(decode_bytes(&SAVE_BASE64.as_bytes()[..len]), None, "{}", len);
+        }
+
+        let compressed = zlib(SAVE_JSON);
+        assert_eq!(decode_bytes(&compressed[..compressed.len() / 2]), None);
+    }
+
+    #[test]
+    fn summarizes_decoded_save() {
+        let summary = summarize(&save_json(), Path::new(""));
+        assert_eq!(summary.gold, Some(123));
+        assert_eq!(summary.map_id, Some(3));
+        assert_eq!(summary.playtime_seconds, Some(121));
+        assert_eq!(summary.party.len(), 2);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::db::Game;
+use crate::save_rules::GlobPattern;
+use crate::watcher::WatchBackend;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// 当前的 save_config 版本；旧版本的配置在读取时按 `migrate` 升级
+pub const SAVE_CONFIG_VERSION: u64 = 1;
+
+/// 所有存档模式共有的配置项
+const COMMON_KEYS: &[&str] = &["version", "include_globs", "exclude_globs", "watch_backend"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SingleFileConfig {
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderConfig {
+    /// 存档文件夹下作为存档根目录的子目录，为 None 时即存档文件夹本身
+    pub folder_name: Option<String>,
+    pub include_extensions: Vec<String>,
+    pub exclude_extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileGroupConfig {
+    /// 为空表示所有扩展名
+    pub extensions: Vec<String>,
+    /// 文件名模式，`{group}` 捕获分组键
+    pub pattern: Option<String>,
+    pub group_by_prefix: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerConfig {
+    /// 为空表示所有扩展名
+    pub container_extensions: Vec<String>,
+    pub inner_extensions: Vec<String>,
+}
+
+/// 各存档模式专有的配置
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ModeConfig {
+    SingleFile(SingleFileConfig),
+    Folder(FolderConfig),
+    FileGroup(FileGroupConfig),
+    Container(ContainerConfig),
+}
+
+/// 解析并校验后的 save_config，序列化后即为数据库中保存的 JSON
+#[derive(Debug, Clone, Serialize)]
+pub struct SaveConfig {
+    pub version: u64,
+    #[serde(flatten)]
+    pub mode: ModeConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_globs: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_globs: Vec<String>,
+    #[serde(skip_serializing_if = "WatchBackend::is_native")]
+    pub watch_backend: WatchBackend,
+}
+
+fn mode_keys(save_mode: &str) -> Result<&'static [&'static str], String> {
+    match save_mode {
+        "single_file" => Ok(&["extensions"]),
+        "folder" => Ok(&["folder_name", "include_extensions", "exclude_extensions"]),
+        "file_group" => Ok(&["extensions", "pattern", "group_by_prefix"]),
+        "container" => Ok(&["container_extensions", "inner_extensions"]),
+        _ => Err(format!(
+            "未知的存档模式: {}（支持 single_file, folder, file_group, container）",
+            save_mode
+        )),
+    }
+}
+
+type JsonMap = serde_json::Map<String, serde_json::Value>;
+
+/// 将旧版本的配置逐步升级到 SAVE_CONFIG_VERSION
+fn migrate(save_mode: &str, config: &mut JsonMap, from_version: u64) -> Result<(), String> {
+    if from_version > SAVE_CONFIG_VERSION {
+        return Err(format!(
+            "存档配置版本 {} 高于当前支持的版本 {}",
+            from_version, SAVE_CONFIG_VERSION
+        ));
+    }
+
+    // 0 -> 1：没有 version 字段的早期配置
+    if from_version < 1 {
+        // 手动编辑的配置中扩展名列表可能写成逗号分隔的字符串
+        for key in ["extensions", "include_extensions", "exclude_extensions", "container_extensions", "inner_extensions"] {
+            if let Some(serde_json::Value::String(list)) = config.get(key) {
+                let items: Vec<serde_json::Value> = list
+                    .split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| serde_json::json!(s))
+                    .collect();
+                config.insert(key.to_string(), serde_json::Value::Array(items));
+            }
+        }
+        // 早期的 single_file 配置缺少扩展名时按 dat 处理
+        if save_mode == "single_file" {
+            let empty = match config.get("extensions") {
+                None | Some(serde_json::Value::Null) => true,
+                Some(serde_json::Value::Array(items)) => items.is_empty(),
+                Some(_) => false,
+            };
+            if empty {
+                config.insert("extensions".to_string(), serde_json::json!(["dat"]));
+            }
+        }
+    }
+
+    config.insert("version".to_string(), serde_json::json!(SAVE_CONFIG_VERSION));
+    Ok(())
+}
+
+/// 读取单个字段，类型不符时返回带字段名的错误
+fn field<T: DeserializeOwned>(config: &JsonMap, key: &str) -> Result<Option<T>, String> {
+    match config.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| format!("配置项 {} 的类型不正确: {}", key, e)),
+    }
+}
+
+/// 读取扩展名列表：去掉开头的 `.` 并转为小写
+fn extension_list(config: &JsonMap, key: &str) -> Result<Vec<String>, String> {
+    let items: Vec<String> = field(config, key)?.unwrap_or_default();
+    let mut extensions = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let ext = item.trim().trim_start_matches('.').to_lowercase();
+        if ext.is_empty() {
+            return Err(format!("配置项 {} 的第 {} 项为空", key, i + 1));
+        }
+        if ext.contains(['/', '\\', '*', '?', '.']) {
+            return Err(format!(
+                "配置项 {} 的第 {} 项 \"{}\" 无效：扩展名不能包含通配符、`.` 或路径分隔符",
+                key,
+                i + 1,
+                item
+            ));
+        }
+        extensions.push(ext);
+    }
+    Ok(extensions)
+}
+
+fn glob_list(config: &JsonMap, key: &str) -> Result<Vec<String>, String> {
+    let items: Vec<String> = field(config, key)?.unwrap_or_default();
+    for (i, item) in items.iter().enumerate() {
+        GlobPattern::parse(item).map_err(|e| format!("配置项 {} 的第 {} 项无效: {}", key, i + 1, e))?;
+    }
+    Ok(items)
+}
+
+fn non_empty_string(config: &JsonMap, key: &str) -> Result<Option<String>, String> {
+    Ok(field::<String>(config, key)?
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty()))
+}
+
+impl SaveConfig {
+    /// 解析、升级并校验存档配置；错误信息指出具体的配置项
+    pub fn parse(save_mode: &str, save_config: &str) -> Result<Self, String> {
+        let allowed = mode_keys(save_mode)?;
+
+        let value: serde_json::Value = serde_json::from_str(save_config)
+            .map_err(|e| format!("存档配置不是有效的 JSON: {}", e))?;
+        let mut config = match value {
+            serde_json::Value::Object(map) => map,
+            _ => return Err("存档配置必须是 JSON 对象".to_string()),
+        };
+
+        let version: u64 = field(&config, "version")?.unwrap_or(0);
+        migrate(save_mode, &mut config, version)?;
+
+        if let Some(unknown) = config
+            .keys()
+            .find(|k| !allowed.contains(&k.as_str()) && !COMMON_KEYS.contains(&k.as_str()))
+        {
+            return Err(format!(
+                "{} 模式不支持配置项 {}（支持: {}）",
+                save_mode,
+                unknown,
+                allowed.iter().chain(COMMON_KEYS).copied().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        let mode = match save_mode {
+            "single_file" => {
+                let extensions = extension_list(&config, "extensions")?;
+                if extensions.is_empty() {
+                    return Err("single_file 模式的 extensions 至少需要一个扩展名".to_string());
+                }
+                ModeConfig::SingleFile(SingleFileConfig { extensions })
+            }
+            "folder" => {
+                let folder_name = non_empty_string(&config, "folder_name")?;
+                if let Some(ref name) = folder_name {
+                    if name.contains(['/', '\\']) || name == "." || name == ".." {
+                        return Err(format!("配置项 folder_name \"{}\" 必须是存档文件夹下的一个子文件夹名", name));
+                    }
+                }
+                ModeConfig::Folder(FolderConfig {
+                    folder_name,
+                    include_extensions: extension_list(&config, "include_extensions")?,
+                    exclude_extensions: extension_list(&config, "exclude_extensions")?,
+                })
+            }
+            "file_group" => {
+                let pattern = non_empty_string(&config, "pattern")?;
+                if let Some(ref p) = pattern {
+                    if p.matches("{group}").count() > 1 {
+                        return Err(format!("配置项 pattern \"{}\" 中只能有一个 {{group}}", p));
+                    }
+                    if p.contains(['/', '\\']) {
+                        return Err(format!("配置项 pattern \"{}\" 只匹配文件名，不能包含路径分隔符", p));
+                    }
+                }
+                ModeConfig::FileGroup(FileGroupConfig {
+                    extensions: extension_list(&config, "extensions")?,
+                    pattern,
+                    group_by_prefix: field(&config, "group_by_prefix")?.unwrap_or(true),
+                })
+            }
+            _ => ModeConfig::Container(ContainerConfig {
+                container_extensions: extension_list(&config, "container_extensions")?,
+                inner_extensions: extension_list(&config, "inner_extensions")?,
+            }),
+        };
+
+        let watch_backend = match non_empty_string(&config, "watch_backend")?.as_deref() {
+            None | Some("native") => WatchBackend::Native,
+            Some("poll") => WatchBackend::Poll,
+            Some(other) => {
+                return Err(format!("配置项 watch_backend 的值 \"{}\" 无效（支持 native, poll）", other));
+            }
+        };
+
+        Ok(Self {
+            version: SAVE_CONFIG_VERSION,
+            mode,
+            include_globs: glob_list(&config, "include_globs")?,
+            exclude_globs: glob_list(&config, "exclude_globs")?,
+            watch_backend,
+        })
+    }
+
+    /// 该存档模式的默认配置
+    pub fn default_for(save_mode: &str) -> Result<Self, String> {
+        Self::parse(save_mode, "{}")
+    }
+
+    /// 游戏当前的存档配置；没有保存配置时使用默认配置
+    pub fn for_game(game: &Game) -> Result<Self, String> {
+        let save_mode = game.save_mode.as_deref().unwrap_or("single_file");
+        match game.save_config.as_deref() {
+            Some(config) => Self::parse(save_mode, config),
+            None => Self::default_for(save_mode),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v0_config_with_comma_separated_extensions_is_migrated() {
+        let config = SaveConfig::parse("single_file", r#"{"extensions": ".DAT, sav"}"#).unwrap();
+        assert_eq!(config.version, SAVE_CONFIG_VERSION);
+        match config.mode {
+            ModeConfig::SingleFile(c) => assert_eq!(c.extensions, vec!["dat", "sav"]),
+            other => panic!("unexpected mode config: {:?}", other),
+        }
+
+        let config = SaveConfig::parse("folder", r#"{"include_extensions": "ks, json", "folder_name": null}"#).unwrap();
+        match config.mode {
+            ModeConfig::Folder(c) => assert_eq!(c.include_extensions, vec!["ks", "json"]),
+            other => panic!("unexpected mode config: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn v0_single_file_without_extensions_defaults_to_dat() {
+        let config = SaveConfig::default_for("single_file").unwrap();
+        match config.mode {
+            ModeConfig::SingleFile(c) => assert_eq!(c.extensions, vec!["dat"]),
+            other => panic!("unexpected mode config: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn comma_separated_extensions_are_rejected_in_v1() {
+        assert!(SaveConfig::parse("single_file", r#"{"version": 1, "extensions": "dat, sav"}"#).is_err());
+    }
+
+    #[test]
+    fn unknown_key_is_rejected() {
+        let err = SaveConfig::parse("single_file", r#"{"version": 1, "extensions": ["dat"], "extension": ["sav"]}"#)
+            .unwrap_err();
+        assert!(err.contains("extension"), "{}", err);
+
+        // 其他模式的配置项同样不被接受
+        assert!(SaveConfig::parse("folder", r#"{"version": 1, "extensions": ["dat"]}"#).is_err());
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let err = SaveConfig::parse("single_file", r#"{"version": 2, "extensions": ["dat"]}"#).unwrap_err();
+        assert!(err.contains('2'), "{}", err);
+    }
+
+    #[test]
+    fn common_keys_round_trip() {
+        let config = SaveConfig::parse(
+            "file_group",
+            r#"{"version": 1, "include_globs": ["save/*.dat"], "exclude_globs": ["*.tmp"], "watch_backend": "poll"}"#,
+        )
+        .unwrap();
+        let reparsed = SaveConfig::parse("file_group", &config.to_json()).unwrap();
+        assert_eq!(reparsed.include_globs, vec!["save/*.dat"]);
+        assert_eq!(reparsed.exclude_globs, vec!["*.tmp"]);
+        assert_eq!(reparsed.watch_backend, WatchBackend::Poll);
+
+        assert!(SaveConfig::parse("file_group", r#"{"include_globs": ["../save"]}"#).is_err());
+        assert!(SaveConfig::parse("file_group", r#"{"watch_backend": "inotify"}"#).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+/// save_config 中 include_globs / exclude_globs 的通配符规则，路径相对于存档文件夹。
+///
+/// - `*` 匹配一个路径层级内的任意字符，`?` 匹配单个字符
+/// - `**` 作为单独的一层时匹配任意多层目录（包括零层）
+/// - 不含 `/` 的规则（如 `*.tmp`）匹配任意层级下的文件名
+/// - 匹配时不区分大小写
+pub struct GlobPattern {
+    /// 配置中的原始写法，用于提示
+    source: String,
+    segments: Vec<String>,
+    /// 规则不含 `/`，只匹配文件名
+    name_only: bool,
+}
+
+impl GlobPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let normalized = pattern.trim().replace('\\', "/");
+        if normalized.is_empty() {
+            return Err("规则不能为空".to_string());
+        }
+        if normalized.starts_with('/') || normalized.contains(':') {
+            return Err(format!("规则必须是相对于存档文件夹的路径: {}", pattern));
+        }
+
+        let segments: Vec<String> = normalized
+            .trim_end_matches('/')
+            .split('/')
+            .map(|s| s.to_lowercase())
+            .collect();
+        for segment in &segments {
+            if segment.is_empty() || segment == "." || segment == ".." {
+                return Err(format!("规则中包含无效的路径层级: {}", pattern));
+            }
+            if segment.contains("**") && segment != "**" {
+                return Err(format!("`**` 必须单独作为一层目录使用: {}", pattern));
+            }
+        }
+
+        Ok(Self {
+            source: pattern.trim().to_string(),
+            name_only: segments.len() == 1 && segments[0] != "**",
+            segments,
+        })
+    }
+
+    /// `relative_path` 为相对于存档文件夹、以 `/` 分隔的路径
+    pub fn matches(&self, relative_path: &str) -> bool {
+        let lower = relative_path.to_lowercase();
+        let parts: Vec<&str> = lower.split('/').filter(|p| !p.is_empty()).collect();
+        if self.name_only {
+            return parts
+                .last()
+                .map(|name| match_segment(&self.segments[0], name))
+                .unwrap_or(false);
+        }
+        match_segments(&self.segments, &parts)
+    }
+}
+
+fn match_segments(pattern: &[String], parts: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => parts.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=parts.len()).any(|skip| match_segments(rest, &parts[skip..]))
+        }
+        Some((first, rest)) => match parts.split_first() {
+            Some((part, remaining)) => {
+                match_segment(first, part) && match_segments(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+/// 单层路径内的 `*` / `?` 匹配
+fn match_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 游戏的 include / exclude 规则；没有 include 规则时接受所有文件，exclude 优先
+#[derive(Default)]
+pub struct SaveRules {
+    include: Vec<GlobPattern>,
+    exclude: Vec<GlobPattern>,
+}
+
+impl SaveRules {
+    /// 规则在保存 save_config 时已校验，这里忽略无法解析的规则
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        let parse_list = |patterns: &[String]| -> Vec<GlobPattern> {
+            patterns.iter().filter_map(|p| GlobPattern::parse(p).ok()).collect()
+        };
+        Self {
+            include: parse_list(include),
+            exclude: parse_list(exclude),
+        }
+    }
+
+    /// 文件被规则拒绝的原因；接受时返回 None
+    pub fn rejection(&self, relative_path: &str) -> Option<String> {
+        if let Some(glob) = self.exclude.iter().find(|g| g.matches(relative_path)) {
+            return Some(format!("匹配 exclude_globs 规则 {}", glob.source));
+        }
+        if self.include.is_empty() || self.include.iter().any(|g| g.matches(relative_path)) {
+            None
+        } else {
+            Some("不匹配任何 include_globs 规则".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(include: &[&str], exclude: &[&str]) -> SaveRules {
+        let to_vec = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        SaveRules::new(&to_vec(include), &to_vec(exclude))
+    }
+
+    #[test]
+    fn slot_glob_with_system_file_excluded() {
+        let slots = rules(&["save/*.dat"], &[]);
+        assert!(slots.rejection("save/file1.dat").is_none());
+        assert!(slots.rejection("save/system.dat").is_none());
+        assert!(slots.rejection("system.dat").is_some());
+        assert!(slots.rejection("save/sub/file1.dat").is_some());
+
+        // 不含 `/` 的 exclude 规则匹配任意层级，且优先于 include
+        let slots = rules(&["save/*.dat"], &["system.dat"]);
+        assert!(slots.rejection("save/system.dat").is_some());
+        assert!(slots.rejection("save/file1.dat").is_none());
+    }
+
+    #[test]
+    fn double_star_matches_zero_or_more_directories() {
+        let glob = GlobPattern::parse("save/**/*.dat").unwrap();
+        assert!(glob.matches("save/file1.dat"));
+        assert!(glob.matches("save/a/file1.dat"));
+        assert!(glob.matches("save/a/b/file1.dat"));
+        assert!(!glob.matches("other/file1.dat"));
+
+        let glob = GlobPattern::parse("**/*.dat").unwrap();
+        assert!(glob.matches("file1.dat"));
+        assert!(glob.matches("a/file1.dat"));
+    }
+
+    #[test]
+    fn matching_is_case_insensitive() {
+        let glob = GlobPattern::parse("Save/*.DAT").unwrap();
+        assert!(glob.matches("save/file1.dat"));
+        assert!(glob.matches("SAVE/FILE1.Dat"));
+
+        let temp = rules(&[], &["*.TMP"]);
+        assert!(temp.rejection("save/File.tmp").is_some());
+    }
+
+    #[test]
+    fn name_only_glob_matches_any_level() {
+        let glob = GlobPattern::parse("*.tmp").unwrap();
+        assert!(glob.matches("a.tmp"));
+        assert!(glob.matches("save/sub/a.tmp"));
+        assert!(!glob.matches("save/a.tmp/b.dat"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        assert!(GlobPattern::parse("").is_err());
+        assert!(GlobPattern::parse("/save/*.dat").is_err());
+        assert!(GlobPattern::parse("C:/save/*.dat").is_err());
+        assert!(GlobPattern::parse("../save/*.dat").is_err());
+        assert!(GlobPattern::parse("save/a**/*.dat").is_err());
+    }
+}
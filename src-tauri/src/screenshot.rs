@@ -126,15 +126,65 @@ impl ScreenshotManager {
 }
 
 #[cfg(target_os = "windows")]
-fn find_window_rect_for_exe(exe_path: &str) -> Option<(i32, i32, i32, i32)> {
-    fn normalize(p: &str) -> String {
-        PathBuf::from(p)
-            .to_string_lossy()
-            .replace('\\', "/")
-            .to_lowercase()
+fn normalize_exe_path(p: &str) -> String {
+    PathBuf::from(p)
+        .to_string_lossy()
+        .replace('\\', "/")
+        .to_lowercase()
+}
+
+#[cfg(target_os = "windows")]
+fn exe_path_for_pid(pid: u32) -> Option<String> {
+    let hproc = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+
+    let mut buf = [0u16; 260];
+    let len = unsafe { K32GetModuleFileNameExW(hproc, None, &mut buf) };
+    let _ = unsafe { CloseHandle(hproc) };
+    if len == 0 {
+        return None;
     }
 
-    let target_norm = normalize(exe_path);
+    Some(String::from_utf16_lossy(&buf[..len as usize]))
+}
+
+/// 游戏进程是否正在运行（与 find_window_rect_for_exe 相同的路径匹配规则，但不要求窗口可见）
+#[cfg(target_os = "windows")]
+pub fn is_exe_running(exe_path: &str) -> bool {
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+
+    let target_norm = normalize_exe_path(exe_path);
+
+    let snapshot = match unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) } {
+        Ok(h) => h,
+        Err(_) => return find_window_rect_for_exe(exe_path).is_some(),
+    };
+
+    let mut entry = PROCESSENTRY32W {
+        dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+
+    let mut found = false;
+    let mut has_entry = unsafe { Process32FirstW(snapshot, &mut entry) }.is_ok();
+    while has_entry {
+        if let Some(exe) = exe_path_for_pid(entry.th32ProcessID) {
+            if normalize_exe_path(&exe) == target_norm {
+                found = true;
+                break;
+            }
+        }
+        has_entry = unsafe { Process32NextW(snapshot, &mut entry) }.is_ok();
+    }
+
+    let _ = unsafe { CloseHandle(snapshot) };
+    found
+}
+
+#[cfg(target_os = "windows")]
+fn find_window_rect_for_exe(exe_path: &str) -> Option<(i32, i32, i32, i32)> {
+    let target_norm = normalize_exe_path(exe_path);
 
     struct SearchCtx {
         target_norm: String,
@@ -156,19 +206,10 @@ fn find_window_rect_for_exe(exe_path: &str) -> Option<(i32, i32, i32, i32)> {
             return BOOL(1);
         }
 
-        let hproc = match unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) } {
-            Ok(h) => h,
-            Err(_) => return BOOL(1),
+        let exe = match exe_path_for_pid(pid) {
+            Some(e) => e,
+            None => return BOOL(1),
         };
-
-        let mut buf = [0u16; 260];
-        let len = unsafe { K32GetModuleFileNameExW(hproc, None, &mut buf) };
-        let _ = unsafe { CloseHandle(hproc) };
-        if len == 0 {
-            return BOOL(1);
-        }
-
-        let exe = String::from_utf16_lossy(&buf[..len as usize]);
         let exe_norm = exe.replace('\\', "/").to_lowercase();
 
         if exe_norm == ctx.target_norm {
@@ -198,6 +239,11 @@ fn find_window_rect_for_exe(exe_path: &str) -> Option<(i32, i32, i32, i32)> {
     ctx.found_rect.map(|r| (r.left, r.top, r.right, r.bottom))
 }
 
+#[cfg(not(target_os = "windows"))]
+pub fn is_exe_running(_exe_path: &str) -> bool {
+    false
+}
+
 #[cfg(not(target_os = "windows"))]
 fn find_window_rect_for_exe(_exe_path: &str) -> Option<(i32, i32, i32, i32)> {
     None
@@ -1,141 +1,1375 @@
-use crate::db::{Database, Snapshot};
+use crate::db::{Database, Game, RecordedFile, Snapshot, SnapshotFileRecord};
+use crate::path_index::{canonical_path, PathIndex};
+use crate::save_config::{FileGroupConfig, FolderConfig, ModeConfig, SaveConfig};
+use crate::save_rules::SaveRules;
+use crate::restore::{RestoreEntry, StaleFile};
+use crate::renpy;
+use crate::rpgmaker;
+use crate::screenshot::is_exe_running;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 use tauri::AppHandle;
 use tauri::Emitter;
 use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// folder / file_group 模式下存档文件在快照目录中的子目录，避免与 metadata.json 等文件冲突
+const SAVE_BACKUP_DIR: &str = "save";
+
+/// file_group 模式中作为缩略图的附属文件扩展名
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "webp"];
+
+/// 检查存档是否写入完成的轮询间隔
+const STABLE_POLL_INTERVAL: Duration = Duration::from_millis(150);
+
+/// 大小和修改时间需要连续保持不变的轮询次数
+const STABLE_POLLS_REQUIRED: u32 = 2;
+
+/// 等待存档写入完成的最长时间
+const STABLE_MAX_WAIT: Duration = Duration::from_secs(15);
+
+/// 复制过程中源文件发生变化时的最大重试次数
+const COPY_MAX_ATTEMPTS: u32 = 3;
+
+/// 从存档中提取的缩略图在快照目录中的文件名
+const EXTRACTED_THUMBNAIL_FILE: &str = "thumbnail.png";
+
+/// 存档配置预览最多列出的文件数
+const MAX_PREVIEW_FILES: usize = 2000;
+
+/// 待写入快照的单个文件
+struct SnapshotFile {
+    source: PathBuf,
+    /// 相对于存档根目录的路径（使用 `/` 分隔）
+    relative_path: String,
+    /// 相对于快照目录的备份路径
+    backup_file: String,
+    /// 恢复时写回的位置；为 None 时即 `source`
+    original_path: Option<PathBuf>,
+}
+
+/// 一次快照的内容描述，由各存档模式生成后交给 `create_snapshot` 写入
+struct SnapshotPlan {
+    save_mode: String,
+    /// 快照类型：auto, pre_restore, manual, startup, resume, tombstone
+    kind: &'static str,
+    /// 为 None 时使用默认名称 "快照 <时间>"
+    name: Option<String>,
+    note: Option<String>,
+    original_save_path: String,
+    files: Vec<SnapshotFile>,
+    /// 合并进 metadata.json 的额外字段
+    metadata: serde_json::Map<String, serde_json::Value>,
+    /// 作为缩略图的文件（相对于快照目录）
+    thumbnail_file: Option<String>,
+}
+
+/// file_group 的文件名模式：`*` 匹配任意字符，`{group}` 匹配并捕获分组键
+enum PatternToken {
+    Literal(char),
+    Star,
+    Group,
+}
+
+/// 快照失败时发送给前端的事件内容
+#[derive(Clone, serde::Serialize)]
+struct SnapshotFailure {
+    game_id: String,
+    error: String,
+}
+
+/// 存档配置预览中单个文件的情况
+#[derive(Debug, serde::Serialize)]
+pub struct SavePreviewItem {
+    /// 相对于存档文件夹的路径（使用 `/` 分隔）
+    pub relative_path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+    /// 会被快照时所属的分组，同一分组的文件一起进入快照；被忽略时为 None
+    pub group: Option<String>,
+    /// 被忽略的原因
+    pub reason: Option<String>,
+}
+
+/// preview_save_config 的结果
+#[derive(Debug, serde::Serialize)]
+pub struct SaveConfigPreview {
+    pub save_mode: String,
+    pub root: String,
+    pub items: Vec<SavePreviewItem>,
+    pub captured_count: usize,
+    pub ignored_count: usize,
+    pub group_count: usize,
+    /// 文件过多，只列出了前 MAX_PREVIEW_FILES 个
+    pub truncated: bool,
+}
+
+fn file_state(path: &Path) -> std::io::Result<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.len(), metadata.modified().ok()))
+}
+
+/// 等待文件写入完成：大小和修改时间在多次轮询中保持不变，且文件可以被打开读取
+/// （游戏以独占方式打开时会出现共享冲突）。已被删除的文件直接跳过。
+pub fn wait_for_stable(paths: &[PathBuf]) -> Result<(), String> {
+    let start = Instant::now();
+    let mut last_states: Vec<Option<(u64, Option<SystemTime>)>> = vec![None; paths.len()];
+    let mut stable_polls = vec![0u32; paths.len()];
+
+    loop {
+        let mut pending = None;
+        for (i, path) in paths.iter().enumerate() {
+            if stable_polls[i] >= STABLE_POLLS_REQUIRED {
+                continue;
+            }
+            match file_state(path) {
+                Ok(state) => {
+                    if last_states[i] == Some(state) && fs::File::open(path).is_ok() {
+                        stable_polls[i] += 1;
+                    } else {
+                        stable_polls[i] = 0;
+                    }
+                    last_states[i] = Some(state);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    stable_polls[i] = STABLE_POLLS_REQUIRED;
+                }
+                Err(_) => {
+                    stable_polls[i] = 0;
+                }
+            }
+            if stable_polls[i] < STABLE_POLLS_REQUIRED && pending.is_none() {
+                pending = Some(path);
+            }
+        }
+
+        let pending = match pending {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if start.elapsed() >= STABLE_MAX_WAIT {
+            return Err(format!("等待存档写入完成超时: {}", pending.to_string_lossy()));
+        }
+        std::thread::sleep(STABLE_POLL_INTERVAL);
+    }
+}
+
+/// 复制文件，若复制期间源文件发生变化则重试；返回复制时源文件的大小和修改时间
+fn copy_consistent(source: &Path, dest: &Path) -> Result<(u64, Option<SystemTime>), Box<dyn std::error::Error>> {
+    for _ in 0..COPY_MAX_ATTEMPTS {
+        let before = file_state(source)?;
+        fs::copy(source, dest)
+            .map_err(|e| format!("复制存档失败 {}: {}", source.to_string_lossy(), e))?;
+        if file_state(source)? == before {
+            return Ok(before);
+        }
+        std::thread::sleep(STABLE_POLL_INTERVAL);
+    }
+    Err(format!("存档在复制过程中持续变化: {}", source.to_string_lossy()).into())
+}
+
+/// 修改时间的 RFC 3339 字符串，快照记录和预览中统一使用这一格式
+pub fn modified_string(modified: Option<SystemTime>) -> Option<String> {
+    modified.map(|t| chrono::DateTime::<Utc>::from(t).to_rfc3339())
+}
+
+/// 小写的文件扩展名，没有扩展名时为 None
+pub fn file_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .filter(|e| !e.is_empty())
+}
+
+pub fn is_image(path: &Path) -> bool {
+    file_extension(path)
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+        .unwrap_or(false)
+}
+
+/// 修改时间最新的文件；用于从多个存档位中选出缩略图、名称和摘要的来源
+fn newest_file<'a>(paths: impl Iterator<Item = &'a PathBuf>) -> Option<&'a PathBuf> {
+    paths.max_by_key(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+}
+
+/// 文件内容的 SHA-256（十六进制）
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 记录中的文件在快照目录中的位置；早期记录没有 backup_file，按快照的目录布局查找
+fn recorded_backup_path(record: &RecordedFile) -> PathBuf {
+    let snapshot_dir = Path::new(&record.backup_save_path);
+    match &record.backup_file {
+        Some(backup_file) => snapshot_dir.join(backup_file),
+        None => {
+            let nested = snapshot_dir.join(SAVE_BACKUP_DIR).join(&record.relative_path);
+            if nested.is_file() {
+                nested
+            } else {
+                snapshot_dir.join(&record.relative_path)
+            }
+        }
+    }
+}
+
 pub struct SnapshotManager {
     db: Database,
     app_handle: AppHandle,
+    path_index: PathIndex,
 }
 
 impl SnapshotManager {
     pub fn new(app_handle: AppHandle) -> Self {
         let db = Database::new(&app_handle);
+        let path_index = PathIndex::build(db.get_games().unwrap_or_default());
         Self {
             db,
             app_handle,
+            path_index,
+        }
+    }
+
+    /// 游戏被添加、修改或删除后重建路径索引
+    pub fn refresh_path_index(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.path_index = PathIndex::build(self.db.get_games()?);
+        Ok(())
+    }
+
+    /// include 为空时表示接受所有扩展名，exclude 优先
+    fn extension_allowed(path: &Path, include: &[String], exclude: &[String]) -> bool {
+        let ext = file_extension(path).unwrap_or_default();
+        if exclude.contains(&ext) {
+            return false;
+        }
+        include.is_empty() || include.contains(&ext)
+    }
+
+    /// 快照目录本身位于游戏目录下，必须忽略，否则会递归地为快照生成快照
+    fn is_inside_visual_logger(path: &Path) -> bool {
+        path.components().any(|c| c.as_os_str() == "visual-logger")
+    }
+
+    fn relative_path_string(path: &Path, root: &Path) -> Option<String> {
+        let rel = path.strip_prefix(root).ok()?;
+        let parts: Vec<String> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("/"))
         }
     }
 
-    fn parse_extensions_from_config(save_config: &Option<String>) -> Vec<String> {
-        let config_str = match save_config {
-            Some(s) => s,
-            None => return vec!["dat".to_string()],
+    fn collect_files(dir: &Path, include: &[String], exclude: &[String], out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if Self::is_inside_visual_logger(&path) {
+                continue;
+            }
+            if path.is_dir() {
+                Self::collect_files(&path, include, exclude, out)?;
+            } else if path.is_file() && Self::extension_allowed(&path, include, exclude) {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn save_root(game: &Game) -> PathBuf {
+        PathBuf::from(game.save_folder_path.as_deref().unwrap_or(&game.game_folder_path))
+    }
+
+    /// folder 模式的存档根目录：配置了 folder_name 时为存档文件夹下的子目录
+    fn folder_root(save_root: &Path, folder: &FolderConfig) -> PathBuf {
+        match folder.folder_name.as_deref() {
+            Some(folder_name) => save_root.join(folder_name),
+            None => save_root.to_path_buf(),
+        }
+    }
+
+    /// 文件属于某个存档分组时返回分组键；图片附属文件总是参与分组，其余文件按 extensions 过滤（为空表示全部）
+    fn file_group_key(path: &Path, group: &FileGroupConfig) -> Option<String> {
+        if !is_image(path) && !Self::extension_allowed(path, &group.extensions, &[]) {
+            return None;
+        }
+        let name = path.file_name()?.to_string_lossy().to_string();
+        Self::group_key(&name, group.pattern.as_deref(), group.group_by_prefix)
+    }
+
+    /// save_config 中的 include_globs / exclude_globs 规则（相对于存档文件夹）拒绝该文件的原因
+    fn rules_rejection(save_root: &Path, config: &SaveConfig, path: &Path) -> Option<String> {
+        let relative_path = Self::relative_path_string(path, save_root)
+            .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        SaveRules::new(&config.include_globs, &config.exclude_globs).rejection(&relative_path)
+    }
+
+    fn rules_allow(game: &Game, config: &SaveConfig, path: &Path) -> bool {
+        Self::rules_rejection(&canonical_path(&Self::save_root(game)), config, &canonical_path(path)).is_none()
+    }
+
+    /// 扩展名过滤拒绝该文件的原因，规则同 extension_allowed
+    fn extension_rejection(path: &Path, include: &[String], include_key: &str, exclude: &[String]) -> Option<String> {
+        let ext = file_extension(path).unwrap_or_default();
+        if exclude.contains(&ext) {
+            Some(format!("扩展名 .{} 在 exclude_extensions 中", ext))
+        } else if include.is_empty() || include.contains(&ext) {
+            None
+        } else if ext.is_empty() {
+            Some(format!("没有扩展名，不在 {} 中", include_key))
+        } else {
+            Some(format!("扩展名 .{} 不在 {} 中", ext, include_key))
+        }
+    }
+
+    /// 文件不会被快照的原因；是存档配置所关心的文件时返回 None
+    fn ignore_reason(save_root: &Path, config: &SaveConfig, path: &Path) -> Option<String> {
+        // 与 PathIndex 一样比较规范化后的路径，保存的存档文件夹路径在大小写、符号链接或 `..` 上
+        // 与事件路径不同时也能正确判断
+        let save_root = &canonical_path(save_root);
+        let path = &canonical_path(path);
+        if Self::is_inside_visual_logger(path) {
+            return Some("位于快照目录 visual-logger 中".to_string());
+        }
+        if !matches!(config.mode, ModeConfig::SingleFile(_)) && !path.starts_with(save_root) {
+            return Some("不在存档文件夹中".to_string());
+        }
+        if let Some(reason) = Self::rules_rejection(save_root, config, path) {
+            return Some(reason);
+        }
+
+        match &config.mode {
+            ModeConfig::Folder(folder) => {
+                if !path.starts_with(canonical_path(&Self::folder_root(save_root, folder))) {
+                    return Some(format!(
+                        "不在 folder_name 指定的子文件夹 {} 中",
+                        folder.folder_name.as_deref().unwrap_or_default()
+                    ));
+                }
+                Self::extension_rejection(path, &folder.include_extensions, "include_extensions", &folder.exclude_extensions)
+            }
+            ModeConfig::FileGroup(group) => {
+                if !is_image(path) {
+                    if let Some(reason) = Self::extension_rejection(path, &group.extensions, "extensions", &[]) {
+                        return Some(reason);
+                    }
+                }
+                match Self::file_group_key(path, group) {
+                    Some(_) => None,
+                    None => Some("文件名不匹配 pattern".to_string()),
+                }
+            }
+            ModeConfig::Container(container) => {
+                Self::extension_rejection(path, &container.container_extensions, "container_extensions", &[])
+            }
+            ModeConfig::SingleFile(single) => Self::extension_rejection(path, &single.extensions, "extensions", &[]),
+        }
+    }
+
+    /// 路径是否是游戏存档配置所关心的文件；存档配置无效时不匹配任何文件
+    fn is_save_path(game: &Game, path: &Path) -> bool {
+        match SaveConfig::for_game(game) {
+            Ok(config) => Self::ignore_reason(&Self::save_root(game), &config, path).is_none(),
+            Err(_) => false,
+        }
+    }
+
+    /// 按给定的存档配置列出存档文件夹中现有的文件会如何被快照，不修改任何文件。
+    /// 文件数超过 MAX_PREVIEW_FILES 时只列出前面的部分
+    pub fn preview_save_config(save_root: &Path, save_mode: &str, config: &SaveConfig) -> Result<SaveConfigPreview, Box<dyn std::error::Error>> {
+        if !save_root.is_dir() {
+            return Err(format!("存档文件夹不存在: {}", save_root.to_string_lossy()).into());
+        }
+
+        let mut paths = Vec::new();
+        Self::collect_files(save_root, &[], &[], &mut paths)?;
+        paths.sort();
+        let truncated = paths.len() > MAX_PREVIEW_FILES;
+        paths.truncate(MAX_PREVIEW_FILES);
+
+        let mut items = Vec::new();
+        for path in &paths {
+            let relative_path = Self::relative_path_string(path, save_root).unwrap_or_default();
+            let meta = fs::metadata(path)?;
+            let reason = Self::ignore_reason(save_root, config, path);
+            let group = match (&reason, &config.mode) {
+                (Some(_), _) => None,
+                // folder 模式每次都为整个存档子文件夹创建快照
+                (None, ModeConfig::Folder(folder)) => Some(folder.folder_name.clone().unwrap_or_else(|| ".".to_string())),
+                (None, ModeConfig::FileGroup(group)) => {
+                    let key = Self::file_group_key(path, group).unwrap_or_default();
+                    let dir = path.parent().and_then(|d| Self::relative_path_string(d, save_root));
+                    Some(match dir {
+                        Some(dir) => format!("{}/{}", dir, key),
+                        None => key,
+                    })
+                }
+                (None, _) => Some(relative_path.clone()),
+            };
+            items.push(SavePreviewItem {
+                relative_path,
+                size: meta.len(),
+                modified: modified_string(meta.modified().ok()),
+                group,
+                reason,
+            });
+        }
+
+        // 只有图片的分组不会生成快照，与 plan_file_group 保持一致
+        if matches!(config.mode, ModeConfig::FileGroup(_)) {
+            let groups_with_save: Vec<String> = items
+                .iter()
+                .filter(|item| !is_image(Path::new(&item.relative_path)))
+                .filter_map(|item| item.group.clone())
+                .collect();
+            for item in items.iter_mut() {
+                if item.group.as_ref().is_some_and(|g| !groups_with_save.contains(g)) {
+                    item.group = None;
+                    item.reason = Some("分组中只有图片文件，单独的截图不构成存档".to_string());
+                }
+            }
+        }
+
+        let mut groups: Vec<String> = items.iter().filter_map(|item| item.group.clone()).collect();
+        groups.sort();
+        groups.dedup();
+
+        Ok(SaveConfigPreview {
+            save_mode: save_mode.to_string(),
+            root: save_root.to_string_lossy().to_string(),
+            captured_count: items.iter().filter(|item| item.group.is_some()).count(),
+            ignored_count: items.iter().filter(|item| item.group.is_none()).count(),
+            group_count: groups.len(),
+            truncated,
+            items,
+        })
+    }
+
+    /// 路径所属的游戏（目录嵌套时为最具体的游戏），不检查是否为存档文件
+    pub fn game_for_path(&self, path: &Path) -> Option<String> {
+        self.path_index.find(path).map(|game| game.id.clone())
+    }
+
+    /// 找到变化路径所属的游戏；路径不是该游戏关心的存档文件时返回 None
+    pub fn match_save_path(&self, changed_file_path: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        if Self::is_inside_visual_logger(changed_file_path) {
+            return Ok(None);
+        }
+
+        let game = match self.path_index.find(changed_file_path) {
+            Some(g) => g,
+            None => return Ok(None),
         };
-        let config: serde_json::Value = match serde_json::from_str(config_str) {
-            Ok(c) => c,
-            Err(_) => return vec!["dat".to_string()],
+
+        if Self::is_save_path(game, changed_file_path) {
+            return Ok(Some(game.id.clone()));
+        }
+        // 已被删除的路径也可能是整个目录，目录名通不过扩展名等规则，其下有快照记录的文件时交给
+        // process_save_batch 记录墓碑；原子保存产生的临时文件、日志等在上面已被过滤掉
+        if !changed_file_path.exists() && !self.recorded_files(game, Some(changed_file_path))?.is_empty() {
+            return Ok(Some(game.id.clone()));
+        }
+        Ok(None)
+    }
+
+    /// 通知前端自动快照失败
+    pub fn report_failure(&self, game_id: &str, error: &str) {
+        let _ = self.app_handle.emit("snapshot-failed", SnapshotFailure {
+            game_id: game_id.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    /// 将同一游戏在一段时间内变化的所有存档文件合并为快照（file_group 模式每个存档位一个）
+    pub fn process_save_batch(&self, game_id: &str, changed_paths: &[PathBuf]) -> Result<Vec<Snapshot>, Box<dyn std::error::Error>> {
+        let game = self.db.get_game(game_id)?;
+        if game.paused || self.db.get_watching_paused()? {
+            return Ok(Vec::new());
+        }
+
+        // 很多引擎会以相同内容反复重写存档（如 system.dat），内容未变的文件不产生快照
+        let changed: Vec<PathBuf> = changed_paths
+            .iter()
+            .filter(|p| p.is_file() && Self::is_save_path(&game, p) && self.content_changed(&game, p))
+            .cloned()
+            .collect();
+        let removed: Vec<PathBuf> = changed_paths
+            .iter()
+            .filter(|p| !p.exists())
+            .cloned()
+            .collect();
+
+        let mut created = Vec::new();
+        if !changed.is_empty() {
+            for plan in self.plan_changes(&game, &changed)? {
+                let snapshot = self.create_snapshot(&game, plan)?;
+                self.app_handle.emit("snapshot-created", &snapshot)?;
+                created.push(snapshot);
+            }
+        }
+
+        if !removed.is_empty() {
+            if let Some(plan) = self.plan_tombstone(&game, &removed, &changed)? {
+                let snapshot = self.create_snapshot(&game, plan)?;
+                self.app_handle.emit("snapshot-created", &snapshot)?;
+                created.push(snapshot);
+            }
+        }
+
+        Ok(created)
+    }
+
+    /// 文件内容是否与最近一次快照中记录的哈希不同；没有记录、最近一次是墓碑记录（文件被删除后
+    /// 又出现）或无法读取时视为已变化
+    fn content_changed(&self, game: &Game, path: &Path) -> bool {
+        match self.latest_record(game, path) {
+            Some(record) if record.kind.as_deref() != Some("tombstone") => {
+                hash_file(path).map(|hash| hash != record.sha256).unwrap_or(true)
+            }
+            _ => true,
+        }
+    }
+
+    /// 该存档文件在最近一次快照中的记录
+    fn latest_record(&self, game: &Game, path: &Path) -> Option<RecordedFile> {
+        self.db
+            .get_latest_file_records(&game.id, Some(&path.to_string_lossy()))
+            .ok()?
+            .into_iter()
+            .find(|record| Path::new(&record.original_path) == path)
+    }
+
+    /// 为被删除或重命名的存档文件创建墓碑记录：保存它们最后一次被快照时的内容，以便恢复。
+    /// 从未被快照过的文件没有可保存的内容，不会产生记录
+    fn plan_tombstone(&self, game: &Game, removed: &[PathBuf], changed: &[PathBuf]) -> Result<Option<SnapshotPlan>, Box<dyn std::error::Error>> {
+        // 删除的可能是整个目录，其中所有已记录的文件都算作被删除；已有墓碑记录的文件不再重复记录
+        let mut recorded = HashMap::new();
+        for removed_path in removed {
+            recorded.extend(self.recorded_files(game, Some(removed_path))?);
+        }
+        let mut targets: Vec<(PathBuf, RecordedFile)> = recorded
+            .into_iter()
+            .filter(|(path, record)| {
+                record.kind.as_deref() != Some("tombstone")
+                    && !path.exists()
+                    && recorded_backup_path(record).is_file()
+            })
+            .collect();
+        if targets.is_empty() {
+            return Ok(None);
+        }
+        targets.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // 同一批次中新出现的、内容与被删除文件相同的文件视为重命名后的文件
+        let changed_hashes: Vec<(&PathBuf, String)> = changed
+            .iter()
+            .filter_map(|p| hash_file(p).ok().map(|h| (p, h)))
+            .collect();
+
+        let save_root = Self::save_root(game);
+        let mut files = Vec::new();
+        let mut tombstones = Vec::new();
+        for (path, record) in &targets {
+            let relative_path = record.relative_path.clone();
+            let renamed_to = changed_hashes
+                .iter()
+                .find(|(_, h)| *h == record.sha256)
+                .map(|(p, _)| p.to_string_lossy().to_string());
+
+            tombstones.push(serde_json::json!({
+                "path": path.to_string_lossy().to_string(),
+                "relative_path": relative_path,
+                "event": if renamed_to.is_some() { "renamed" } else { "deleted" },
+                "renamed_to": renamed_to,
+                "source_snapshot_id": record.snapshot_id,
+            }));
+            files.push(SnapshotFile {
+                source: recorded_backup_path(record),
+                backup_file: format!("{}/{}", SAVE_BACKUP_DIR, relative_path),
+                relative_path,
+                original_path: Some(path.clone()),
+            });
+        }
+
+        let name = match (files.as_slice(), tombstones.first().and_then(|t| t.get("renamed_to")).and_then(|v| v.as_str())) {
+            ([file], Some(renamed_to)) => format!(
+                "已重命名: {} → {}",
+                file.relative_path,
+                Path::new(renamed_to).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+            ),
+            ([file], None) => format!("已删除: {}", file.relative_path),
+            _ => format!("已删除 {} 个存档文件", files.len()),
         };
-        let arr = match config.get("extensions").and_then(|v| v.as_array()) {
-            Some(a) => a,
-            None => return vec!["dat".to_string()],
+        let original_save_path = match targets.as_slice() {
+            [(path, _)] => path.to_string_lossy().to_string(),
+            _ => save_root.to_string_lossy().to_string(),
         };
-        let mut ext_list: Vec<String> = arr
-            .iter()
-            .filter_map(|v| v.as_str())
-            .map(|s| s.to_lowercase().to_string())
-            .filter(|s| !s.is_empty())
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("tombstones".to_string(), serde_json::json!(tombstones));
+
+        Ok(Some(SnapshotPlan {
+            save_mode: game.save_mode.clone().unwrap_or_else(|| "single_file".to_string()),
+            kind: "tombstone",
+            name: Some(name),
+            note: None,
+            original_save_path,
+            files,
+            metadata: extra,
+            thumbnail_file: None,
+        }))
+    }
+
+    /// 按游戏的存档模式为变化的文件生成快照内容；file_group 模式每个存档位一个快照，其他模式最多一个
+    fn plan_changes(&self, game: &Game, changed: &[PathBuf]) -> Result<Vec<SnapshotPlan>, Box<dyn std::error::Error>> {
+        let save_mode = game.save_mode.as_deref().unwrap_or("single_file");
+        let plan = match save_mode {
+            "single_file" => Self::plan_single_file(game, changed)?,
+            "folder" => Self::plan_folder(game)?,
+            "file_group" => return Self::plan_file_group(game, changed),
+            "container" => self.plan_container(game, changed)?,
+            _ => None,
+        };
+        Ok(plan.into_iter().collect())
+    }
+
+    /// 每个存档文件最近一次被快照记录时的状态（以原始路径为键）；`under` 不为空时只包含该路径及其下的文件
+    fn recorded_files(&self, game: &Game, under: Option<&Path>) -> Result<HashMap<PathBuf, RecordedFile>, Box<dyn std::error::Error>> {
+        let under = under.map(|p| p.to_string_lossy().to_string());
+        Ok(self
+            .db
+            .get_latest_file_records(&game.id, under.as_deref())?
+            .into_iter()
+            .map(|record| (PathBuf::from(&record.original_path), record))
+            .collect())
+    }
+
+    /// 存档文件与最近一次快照中的记录是否不同：大小和修改时间一致时视为未变化，否则比较内容哈希。
+    /// 最近一次是墓碑记录时，文件是被删除后又出现的，视为已变化
+    fn differs_from_record(path: &Path, record: Option<&RecordedFile>) -> Result<bool, Box<dyn std::error::Error>> {
+        let record = match record {
+            Some(r) if r.kind.as_deref() != Some("tombstone") => r,
+            _ => return Ok(true),
+        };
+
+        let (size, modified) = file_state(path)?;
+        if size as i64 != record.size {
+            return Ok(true);
+        }
+        if record.modified.is_some() && modified_string(modified) == record.modified {
+            return Ok(false);
+        }
+        Ok(hash_file(path)? != record.sha256)
+    }
+
+    /// 检查未被监视期间发生变化的存档，并为其创建快照。
+    /// `kind` 为 startup（应用启动时）或 resume（恢复监视时）
+    pub fn reconcile_game(&self, game: &Game, kind: &'static str) -> Result<Vec<Snapshot>, Box<dyn std::error::Error>> {
+        let (_, paths) = match Self::collect_save_set(game) {
+            Ok(set) => set,
+            // 存档文件夹尚未创建（游戏还没有存过档）
+            Err(_) => return Ok(Vec::new()),
+        };
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let recorded = self.recorded_files(game, None)?;
+        let mut changed = Vec::new();
+        for path in paths {
+            if Self::differs_from_record(&path, recorded.get(&path))? {
+                changed.push(path);
+            }
+        }
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (name, flag) = match kind {
+            "resume" => ("恢复监视时检测到的变化", "detected_on_resume"),
+            _ => ("启动时检测到的变化", "detected_at_startup"),
+        };
+        let mut snapshots = Vec::new();
+        for mut plan in self.plan_changes(game, &changed)? {
+            plan.kind = kind;
+            plan.name = Some(format!("{} {}", name, Utc::now().format("%Y-%m-%d %H:%M:%S")));
+            plan.metadata.insert(flag.to_string(), serde_json::json!(true));
+
+            let snapshot = self.create_snapshot(game, plan)?;
+
+            self.app_handle.emit("snapshot-created", &snapshot)?;
+
+            snapshots.push(snapshot);
+        }
+
+        Ok(snapshots)
+    }
+
+    fn plan_single_file(game: &Game, changed: &[PathBuf]) -> Result<Option<SnapshotPlan>, Box<dyn std::error::Error>> {
+        let save_root = Self::save_root(game);
+
+        let (files, original_save_path, extra) = if let [changed_file_path] = changed {
+            let dat_file_name = changed_file_path.file_name()
+                .ok_or("Invalid file name")?
+                .to_str()
+                .ok_or("Invalid file name encoding")?
+                .to_string();
+
+            let files = vec![SnapshotFile {
+                source: changed_file_path.clone(),
+                relative_path: dat_file_name.clone(),
+                backup_file: dat_file_name.clone(),
+                original_path: None,
+            }];
+
+            let mut extra = serde_json::Map::new();
+            extra.insert("dat_file".to_string(), serde_json::json!(dat_file_name));
+            extra.insert("dat_path".to_string(), serde_json::json!(changed_file_path.to_string_lossy().to_string()));
+
+            (files, changed_file_path.to_string_lossy().to_string(), extra)
+        } else {
+            // 同一批次中写入了多个存档文件
+            let files = changed
+                .iter()
+                .filter_map(|source| {
+                    let relative_path = Self::relative_path_string(source, &save_root)
+                        .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))?;
+                    Some(SnapshotFile {
+                        source: source.clone(),
+                        backup_file: format!("{}/{}", SAVE_BACKUP_DIR, relative_path),
+                        relative_path,
+                        original_path: None,
+                    })
+                })
+                .collect();
+
+            let mut extra = serde_json::Map::new();
+            extra.insert("source_root".to_string(), serde_json::json!(save_root.to_string_lossy().to_string()));
+
+            (files, save_root.to_string_lossy().to_string(), extra)
+        };
+
+        Ok(Some(SnapshotPlan {
+            save_mode: "single_file".to_string(),
+            kind: "auto",
+            name: None,
+            note: None,
+            original_save_path,
+            files,
+            metadata: extra,
+            thumbnail_file: None,
+        }))
+    }
+
+    /// folder 模式：存档目录中任一文件变化时，将整个目录作为一个整体快照
+    fn plan_folder(game: &Game) -> Result<Option<SnapshotPlan>, Box<dyn std::error::Error>> {
+        let config = SaveConfig::for_game(game)?;
+        let folder = match &config.mode {
+            ModeConfig::Folder(folder) => folder,
+            _ => return Ok(None),
+        };
+        let root = Self::folder_root(&Self::save_root(game), folder);
+
+        let mut paths = Vec::new();
+        Self::collect_files(&root, &folder.include_extensions, &folder.exclude_extensions, &mut paths)?;
+        paths.retain(|p| Self::rules_allow(game, &config, p));
+        paths.sort();
+
+        let files: Vec<SnapshotFile> = paths
+            .into_iter()
+            .filter_map(|source| {
+                let relative_path = Self::relative_path_string(&source, &root)?;
+                Some(SnapshotFile {
+                    backup_file: format!("{}/{}", SAVE_BACKUP_DIR, relative_path),
+                    relative_path,
+                    source,
+                    original_path: None,
+                })
+            })
             .collect();
-        if ext_list.is_empty() {
-            ext_list.push("dat".to_string());
+
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("source_root".to_string(), serde_json::json!(root.to_string_lossy().to_string()));
+
+        Ok(Some(SnapshotPlan {
+            save_mode: "folder".to_string(),
+            kind: "auto",
+            name: None,
+            note: None,
+            original_save_path: root.to_string_lossy().to_string(),
+            files,
+            metadata: extra,
+            thumbnail_file: None,
+        }))
+    }
+
+    fn parse_pattern(pattern: &str) -> Vec<PatternToken> {
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("{group}") {
+                tokens.push(PatternToken::Group);
+                rest = after;
+                continue;
+            }
+            let mut chars = rest.chars();
+            match chars.next() {
+                Some('*') => tokens.push(PatternToken::Star),
+                Some(c) => tokens.extend(c.to_lowercase().map(PatternToken::Literal)),
+                None => break,
+            }
+            rest = chars.as_str();
         }
-        ext_list
+        tokens
     }
 
-    pub fn process_save_event(&self, changed_file_path: &PathBuf, last_snapshot_time: Arc<Mutex<Instant>>) -> Result<(), Box<dyn std::error::Error>> {
-        let file_ext = changed_file_path
-            .extension()
-            .map(|e| e.to_string_lossy().to_lowercase().to_string());
+    /// 匹配成功时返回 `{group}` 捕获的字符范围（无 `{group}` 时为 None）
+    fn match_pattern(tokens: &[PatternToken], name: &[char]) -> Option<Option<(usize, usize)>> {
+        let shift = |cap: Option<(usize, usize)>, n: usize| cap.map(|(s, e)| (s + n, e + n));
+        match tokens.first() {
+            None => if name.is_empty() { Some(None) } else { None },
+            Some(PatternToken::Literal(c)) => {
+                if name.first() == Some(c) {
+                    Self::match_pattern(&tokens[1..], &name[1..]).map(|cap| shift(cap, 1))
+                } else {
+                    None
+                }
+            }
+            Some(PatternToken::Star) => (0..=name.len()).find_map(|n| {
+                Self::match_pattern(&tokens[1..], &name[n..]).map(|cap| shift(cap, n))
+            }),
+            Some(PatternToken::Group) => (1..=name.len()).find_map(|n| {
+                Self::match_pattern(&tokens[1..], &name[n..]).map(|_| Some((0, n)))
+            }),
+        }
+    }
 
-        let games = self.db.get_games()?;
-        let mut target_game = None;
+    /// 计算文件所属分组的键；不属于任何分组时返回 None
+    fn group_key(file_name: &str, pattern: Option<&str>, group_by_prefix: bool) -> Option<String> {
+        let lower = file_name.to_lowercase();
+        match pattern {
+            Some(pattern) => {
+                let tokens = Self::parse_pattern(pattern);
+                let name: Vec<char> = lower.chars().collect();
+                let capture = Self::match_pattern(&tokens, &name)?;
+                match capture {
+                    Some((start, end)) => Some(name[start..end].iter().collect()),
+                    None => lower.split('.').next().map(|s| s.to_string()),
+                }
+            }
+            None if group_by_prefix => lower.split('.').next().map(|s| s.to_string()),
+            None => Some(lower),
+        }
+    }
 
-        for game in games {
-            let mut matched = false;
+    /// file_group 模式：同一存档位的多个文件（如 save_001.dat + save_001.png）作为一个整体，
+    /// 同一批次中变化的每个存档位各生成一个快照，以该存档位的截图作为缩略图
+    fn plan_file_group(game: &Game, changed: &[PathBuf]) -> Result<Vec<SnapshotPlan>, Box<dyn std::error::Error>> {
+        let config = SaveConfig::for_game(game)?;
+        let group = match &config.mode {
+            ModeConfig::FileGroup(group) => group,
+            _ => return Ok(Vec::new()),
+        };
+        let save_root = Self::save_root(game);
 
-            if let Some(ref save_folder) = game.save_folder_path {
-                let base = Path::new(save_folder);
-                if changed_file_path.starts_with(base) {
-                    matched = true;
+        let mut groups: Vec<(PathBuf, String)> = Vec::new();
+        for path in changed {
+            let (dir, key) = match (path.parent(), Self::file_group_key(path, group)) {
+                (Some(dir), Some(key)) => (dir.to_path_buf(), key),
+                _ => continue,
+            };
+            if !groups.contains(&(dir.clone(), key.clone())) {
+                groups.push((dir, key));
+            }
+        }
+
+        let mut plans = Vec::new();
+        for (dir, key) in &groups {
+            let mut members = Vec::new();
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_file()
+                    && Self::file_group_key(&path, group).as_deref() == Some(key.as_str())
+                    && Self::rules_allow(game, &config, &path)
+                {
+                    members.push(path);
                 }
             }
+            members.sort();
+
+            // 至少要有一个非图片文件，单独的截图不构成存档
+            let primary = match members.iter().find(|p| !is_image(p)) {
+                Some(primary) => primary.to_string_lossy().to_string(),
+                None => continue,
+            };
 
-            if !matched {
-                let base = Path::new(&game.game_folder_path);
-                if changed_file_path.starts_with(base) {
-                    matched = true;
+            // 存档位有多张图片时取最近写入的
+            let thumbnail_source = newest_file(members.iter().filter(|p| is_image(p))).cloned();
+            let mut thumbnail_file = None;
+            let mut files = Vec::new();
+            for source in members {
+                let relative_path = match Self::relative_path_string(&source, &save_root) {
+                    Some(r) => r,
+                    None => continue,
+                };
+                let backup_file = format!("{}/{}", SAVE_BACKUP_DIR, relative_path);
+                if thumbnail_source.as_ref() == Some(&source) {
+                    thumbnail_file = Some(backup_file.clone());
                 }
+                files.push(SnapshotFile {
+                    source,
+                    relative_path,
+                    backup_file,
+                    original_path: None,
+                });
             }
 
-            if matched {
-                target_game = Some(game);
-                break;
+            let mut extra = serde_json::Map::new();
+            extra.insert("source_root".to_string(), serde_json::json!(save_root.to_string_lossy().to_string()));
+            extra.insert("group_keys".to_string(), serde_json::json!([key]));
+
+            plans.push(SnapshotPlan {
+                save_mode: "file_group".to_string(),
+                kind: "auto",
+                name: None,
+                note: None,
+                original_save_path: primary,
+                files,
+                metadata: extra,
+                thumbnail_file,
+            });
+        }
+
+        Ok(plans)
+    }
+
+    /// 读取 zip 格式容器的内部条目列表；非 zip 容器（引擎私有封包）返回 None
+    fn list_container_entries(path: &Path, inner_extensions: &[String]) -> Option<Vec<serde_json::Value>> {
+        let file = fs::File::open(path).ok()?;
+        let mut archive = zip::ZipArchive::new(file).ok()?;
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let entry = match archive.by_index_raw(i) {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            if !inner_extensions.is_empty() && !Self::extension_allowed(Path::new(&name), inner_extensions, &[]) {
+                continue;
             }
+            entries.push(serde_json::json!({
+                "name": name,
+                "size": entry.size(),
+                "compressed_size": entry.compressed_size(),
+                "crc32": entry.crc32(),
+            }));
         }
+        Some(entries)
+    }
 
-        let game = match target_game {
-            Some(g) => g,
-            None => {
-                return Ok(());
+    /// 该存档文件最近一次快照（不含恢复前快照）的 metadata.json，以及文件在其中的相对路径
+    fn previous_metadata(&self, game: &Game, path: &Path) -> Option<(serde_json::Value, String)> {
+        let record = self.latest_record(game, path)?;
+        let metadata_path = Path::new(&record.backup_save_path).join("metadata.json");
+        let metadata = serde_json::from_str(&fs::read_to_string(metadata_path).ok()?).ok()?;
+        Some((metadata, record.relative_path))
+    }
+
+    /// 同一容器文件上一次快照记录的内部条目列表
+    fn previous_container_entries(&self, game: &Game, path: &Path) -> Option<Vec<serde_json::Value>> {
+        let (metadata, relative_path) = self.previous_metadata(game, path)?;
+        metadata.get("containers")?.get(&relative_path)?.get("inner_entries")?.as_array().cloned()
+    }
+
+    /// 同一 RPG Maker 存档上一次快照记录的摘要
+    fn previous_rpgmaker_summary(&self, game: &Game, path: &Path) -> Option<rpgmaker::SaveSummary> {
+        let (metadata, relative_path) = self.previous_metadata(game, path)?;
+        let entry = metadata
+            .get("rpgmaker_saves")?
+            .as_array()?
+            .iter()
+            .find(|e| e.get("relative_path").and_then(|r| r.as_str()) == Some(relative_path.as_str()))?;
+        serde_json::from_value(entry.get("summary")?.clone()).ok()
+    }
+
+    /// 按条目名比较两次快照的内部条目，大小或 CRC 不同视为修改
+    fn diff_container_entries(previous: &[serde_json::Value], current: &[serde_json::Value]) -> serde_json::Value {
+        let key = |e: &serde_json::Value| e.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string();
+        let signature = |e: &serde_json::Value| (e.get("size").cloned(), e.get("crc32").cloned());
+
+        let mut added = Vec::new();
+        let mut modified = Vec::new();
+        for entry in current {
+            match previous.iter().find(|p| key(p) == key(entry)) {
+                None => added.push(key(entry)),
+                Some(p) if signature(p) != signature(entry) => modified.push(key(entry)),
+                Some(_) => {}
+            }
+        }
+        let removed: Vec<String> = previous
+            .iter()
+            .filter(|p| !current.iter().any(|c| key(c) == key(p)))
+            .map(key)
+            .collect();
+
+        serde_json::json!({
+            "added": added,
+            "removed": removed,
+            "modified": modified,
+        })
+    }
+
+    /// container 模式：容器文件整体快照，并在 metadata 中按容器记录内部条目
+    fn plan_container(&self, game: &Game, changed: &[PathBuf]) -> Result<Option<SnapshotPlan>, Box<dyn std::error::Error>> {
+        let config = SaveConfig::for_game(game)?;
+        let inner_extensions = match &config.mode {
+            ModeConfig::Container(container) => &container.inner_extensions,
+            _ => return Ok(None),
+        };
+        let save_root = Self::save_root(game);
+
+        let mut files = Vec::new();
+        let mut containers = serde_json::Map::new();
+        for changed_file_path in changed {
+            let relative_path = match Self::relative_path_string(changed_file_path, &save_root) {
+                Some(r) => r,
+                None => continue,
+            };
+
+            let mut info = serde_json::Map::new();
+            match Self::list_container_entries(changed_file_path, inner_extensions) {
+                Some(entries) => {
+                    info.insert("inner_format".to_string(), serde_json::json!("zip"));
+                    if let Some(previous) = self.previous_container_entries(game, changed_file_path) {
+                        info.insert("inner_changes".to_string(), Self::diff_container_entries(&previous, &entries));
+                    }
+                    info.insert("inner_entries".to_string(), serde_json::json!(entries));
+                }
+                None => {
+                    info.insert("inner_format".to_string(), serde_json::json!("unknown"));
+                }
             }
+            containers.insert(relative_path.clone(), serde_json::Value::Object(info));
+
+            files.push(SnapshotFile {
+                source: changed_file_path.clone(),
+                backup_file: format!("{}/{}", SAVE_BACKUP_DIR, relative_path),
+                relative_path,
+                original_path: None,
+            });
+        }
+
+        let original_save_path = match files.as_slice() {
+            [] => return Ok(None),
+            [only] => only.source.to_string_lossy().to_string(),
+            _ => save_root.to_string_lossy().to_string(),
         };
 
-        // 只处理 single_file 模式（或其他模式暂未实现时跳过）
-        let save_mode = game.save_mode.as_deref().unwrap_or("single_file");
-        if save_mode != "single_file" {
-            return Ok(());
+        let mut extra = serde_json::Map::new();
+        extra.insert("source_root".to_string(), serde_json::json!(save_root.to_string_lossy().to_string()));
+        extra.insert("containers".to_string(), serde_json::Value::Object(containers));
+
+        Ok(Some(SnapshotPlan {
+            save_mode: "container".to_string(),
+            kind: "auto",
+            name: None,
+            note: None,
+            original_save_path,
+            files,
+            metadata: extra,
+            thumbnail_file: None,
+        }))
+    }
+
+    /// 按存档模式列出游戏当前的全部存档文件，返回存档根目录与文件列表
+    fn collect_save_set(game: &Game) -> Result<(PathBuf, Vec<PathBuf>), Box<dyn std::error::Error>> {
+        let config = SaveConfig::for_game(game)?;
+        let save_root = Self::save_root(game);
+
+        let root = match &config.mode {
+            ModeConfig::Folder(folder) => Self::folder_root(&save_root, folder),
+            _ => save_root,
+        };
+        if !root.is_dir() {
+            return Err(format!("存档文件夹不存在: {}", root.to_string_lossy()).into());
         }
 
-        // 从 save_config 解析扩展名列表
-        let extensions = Self::parse_extensions_from_config(&game.save_config);
-        if extensions.is_empty() {
-            return Ok(());
+        let mut paths = Vec::new();
+        Self::collect_files(&root, &[], &[], &mut paths)?;
+        paths.retain(|p| Self::is_save_path(game, p));
+        paths.sort();
+
+        Ok((root, paths))
+    }
+
+    /// 游戏当前的全部存档文件；存档文件夹不存在或配置无效时为空
+    pub fn current_save_files(game: &Game) -> Vec<PathBuf> {
+        Self::collect_save_set(game).map(|(_, paths)| paths).unwrap_or_default()
+    }
+
+    /// 手动为游戏当前的整套存档创建快照
+    pub fn create_manual_snapshot(&self, game: &Game, name: Option<String>, note: Option<String>) -> Result<Snapshot, Box<dyn std::error::Error>> {
+        let (root, paths) = Self::collect_save_set(game)?;
+        if paths.is_empty() {
+            return Err("存档文件夹中没有符合存档配置的文件".into());
         }
 
-        // 检查文件扩展名是否在配置的列表中
-        let file_ext = match file_ext {
-            Some(ext) => ext,
-            None => return Ok(()),
+        let save_mode = game.save_mode.clone().unwrap_or_else(|| "single_file".to_string());
+        // 缩略图取最近写入的截图，即当前进度所在的存档位
+        let thumbnail_source = match save_mode.as_str() {
+            "file_group" => newest_file(paths.iter().filter(|p| is_image(p))).cloned(),
+            _ => None,
         };
-        if !extensions.contains(&file_ext) {
-            return Ok(());
+        let mut thumbnail_file = None;
+        let mut files = Vec::new();
+        for source in paths {
+            let relative_path = match Self::relative_path_string(&source, &root) {
+                Some(r) => r,
+                None => continue,
+            };
+            let backup_file = format!("{}/{}", SAVE_BACKUP_DIR, relative_path);
+            if thumbnail_source.as_ref() == Some(&source) {
+                thumbnail_file = Some(backup_file.clone());
+            }
+            files.push(SnapshotFile {
+                source,
+                relative_path,
+                backup_file,
+                original_path: None,
+            });
         }
 
+        let mut extra = serde_json::Map::new();
+        extra.insert("source_root".to_string(), serde_json::json!(root.to_string_lossy().to_string()));
+
+        let snapshot = self.create_snapshot(game, SnapshotPlan {
+            save_mode,
+            kind: "manual",
+            name: name.filter(|n| !n.trim().is_empty()),
+            note: note.filter(|n| !n.trim().is_empty()),
+            original_save_path: root.to_string_lossy().to_string(),
+            files,
+            metadata: extra,
+            thumbnail_file,
+        })?;
+
+        self.app_handle.emit("snapshot-created", &snapshot)?;
+
+        Ok(snapshot)
+    }
+
+    /// 当前正在运行的游戏（快捷键使用）
+    pub fn find_running_game(&self) -> Result<Game, Box<dyn std::error::Error>> {
+        let games = self.db.get_games()?;
+        let checked = games.len();
+
+        games.into_iter()
+            .find(|game| {
+                game.exe_path.as_deref()
+                    .map(is_exe_running)
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| format!("没有正在运行的游戏（已检查 {} 个游戏）", checked).into())
+    }
+
+    /// 恢复前为即将被覆盖或删除的存档创建安全快照；这些文件都不存在时返回 None
+    pub fn create_pre_restore_snapshot(&self, game: &Game, restoring: &Snapshot, entries: &[RestoreEntry], stale: &[StaleFile]) -> Result<Option<Snapshot>, Box<dyn std::error::Error>> {
+        let files: Vec<SnapshotFile> = entries
+            .iter()
+            .map(|e| (&e.relative_path, &e.target_path))
+            .chain(stale.iter().map(|f| (&f.relative_path, &f.target_path)))
+            .filter(|(_, target)| target.is_file())
+            .map(|(relative_path, target)| SnapshotFile {
+                source: target.clone(),
+                relative_path: relative_path.clone(),
+                backup_file: format!("{}/{}", SAVE_BACKUP_DIR, relative_path),
+                original_path: None,
+            })
+            .collect();
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("restore_of".to_string(), serde_json::json!(restoring.id));
+        extra.insert("restore_of_name".to_string(), serde_json::json!(restoring.name));
+
+        let snapshot = self.create_snapshot(game, SnapshotPlan {
+            save_mode: game.save_mode.clone().unwrap_or_else(|| "single_file".to_string()),
+            kind: "pre_restore",
+            name: Some(format!("恢复前快照 {}", Utc::now().format("%Y-%m-%d %H:%M:%S"))),
+            note: None,
+            original_save_path: restoring.original_save_path.clone(),
+            files,
+            metadata: extra,
+            thumbnail_file: None,
+        })?;
+
+        self.app_handle.emit("snapshot-created", &snapshot)?;
+
+        Ok(Some(snapshot))
+    }
+
+    /// 创建快照目录，复制文件并写入 metadata.json 与数据库记录
+    fn create_snapshot(&self, game: &Game, plan: SnapshotPlan) -> Result<Snapshot, Box<dyn std::error::Error>> {
         let game_folder = PathBuf::from(&game.game_folder_path);
         let snapshots_dir = game_folder.join("visual-logger").join("snapshots");
         fs::create_dir_all(&snapshots_dir)?;
 
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
         let snapshot_folder_name = format!("{}_{}", game.name, timestamp);
-        let snapshot_folder = snapshots_dir.join(&snapshot_folder_name);
+        let mut snapshot_folder = snapshots_dir.join(&snapshot_folder_name);
+        let mut counter = 1;
+        while snapshot_folder.exists() {
+            snapshot_folder = snapshots_dir.join(format!("{}_{}", snapshot_folder_name, counter));
+            counter += 1;
+        }
         fs::create_dir_all(&snapshot_folder)?;
 
         let uuid = Uuid::new_v4().to_string();
-        
-        std::thread::sleep(std::time::Duration::from_millis(200));
 
-        let dat_file_name = changed_file_path.file_name()
-            .ok_or("Invalid file name")?
-            .to_str()
-            .ok_or("Invalid file name encoding")?;
-        let dat_backup_path = snapshot_folder.join(dat_file_name);
-        fs::copy(changed_file_path, &dat_backup_path)?;
+        let mut file_entries = Vec::new();
+        let mut file_records = Vec::new();
+        // 与 plan.files 一一对应的复制时的修改时间
+        let mut modified_times = Vec::new();
+        for file in &plan.files {
+            let backup_path = snapshot_folder.join(&file.backup_file);
+            let copied = backup_path
+                .parent()
+                .map(fs::create_dir_all)
+                .unwrap_or(Ok(()))
+                .map_err(|e| e.into())
+                .and_then(|_| copy_consistent(&file.source, &backup_path));
+            let hashed = copied.and_then(|state| Ok((state, hash_file(&backup_path)?)));
+            let ((size, modified), sha256) = match hashed {
+                Ok(result) => result,
+                Err(e) => {
+                    // 不留下不完整的快照目录
+                    let _ = fs::remove_dir_all(&snapshot_folder);
+                    return Err(e);
+                }
+            };
+            let original_path = file.original_path.as_ref().unwrap_or(&file.source).to_string_lossy().to_string();
+            file_entries.push(serde_json::json!({
+                "relative_path": file.relative_path,
+                "backup_file": file.backup_file,
+                "original_path": original_path,
+                "size": size,
+                "modified": modified_string(modified),
+                "sha256": sha256,
+            }));
+            file_records.push(SnapshotFileRecord {
+                original_path,
+                relative_path: file.relative_path.clone(),
+                backup_file: file.backup_file.clone(),
+                size: size as i64,
+                modified: modified_string(modified),
+                sha256,
+            });
+            modified_times.push(modified);
+        }
+
+        // Ren'Py 存档内嵌了截图和存档名；从备份副本读取，保证与快照中的存档一致。
+        // 快照包含多个存档位时（如 folder 模式），名称和缩略图取最近写入的存档
+        let mut thumbnail_file = plan.thumbnail_file;
+        let mut name = plan.name;
+        let mut renpy_saves = Vec::new();
+        let mut newest_renpy: Option<(Option<SystemTime>, renpy::RenpySave)> = None;
+        for (file, modified) in plan.files.iter().zip(&modified_times).filter(|(f, _)| renpy::is_renpy_save(&f.source)) {
+            let save = match renpy::read_renpy_save(&snapshot_folder.join(&file.backup_file)) {
+                Some(save) => save,
+                None => continue,
+            };
+            renpy_saves.push(serde_json::json!({
+                "relative_path": file.relative_path,
+                "save_name": save.save_name,
+                "json": save.json,
+            }));
+            if newest_renpy.as_ref().is_none_or(|(newest, _)| modified > newest) {
+                newest_renpy = Some((*modified, save));
+            }
+        }
+        if let Some((_, save)) = newest_renpy {
+            if thumbnail_file.is_none() {
+                if let Some(ref png) = save.screenshot {
+                    if fs::write(snapshot_folder.join(EXTRACTED_THUMBNAIL_FILE), png).is_ok() {
+                        thumbnail_file = Some(EXTRACTED_THUMBNAIL_FILE.to_string());
+                    }
+                }
+            }
+            if name.is_none() {
+                name = save.save_name;
+            }
+        }
+
+        // RPG Maker 存档解码为摘要，并与同一存档上一次快照的摘要比较开关和变量；
+        // 快照的摘要与 Ren'Py 的名称一样取最近写入的存档
+        let mut summary: Option<(Option<SystemTime>, String)> = None;
+        let mut rpgmaker_saves = Vec::new();
+        for (file, modified) in plan.files.iter().zip(&modified_times).filter(|(f, _)| rpgmaker::is_rpgmaker_save(&f.source)) {
+            let save = match rpgmaker::decode_save(&snapshot_folder.join(&file.backup_file)) {
+                Ok(save) => save,
+                Err(e) => {
+                    rpgmaker_saves.push(serde_json::json!({ "relative_path": file.relative_path, "error": e }));
+                    continue;
+                }
+            };
+            // config / global 存档没有游戏进度
+            if save.get("party").is_none() {
+                continue;
+            }
+            let current = rpgmaker::summarize(&save, &game_folder);
+            let original_path = file.original_path.as_ref().unwrap_or(&file.source);
+            let changes = self
+                .previous_rpgmaker_summary(game, original_path)
+                .map(|previous| rpgmaker::diff(&previous, &current));
+            let description = rpgmaker::describe(&current, changes.as_ref());
+            if !description.is_empty() && summary.as_ref().is_none_or(|(newest, _)| modified > newest) {
+                summary = Some((*modified, description));
+            }
+            rpgmaker_saves.push(serde_json::json!({
+                "relative_path": file.relative_path,
+                "summary": current,
+                "changes": changes,
+            }));
+        }
 
         let default_name = format!("快照 {}", Utc::now().format("%Y-%m-%d %H:%M:%S"));
 
-        let metadata = serde_json::json!({
+        let mut metadata = serde_json::json!({
             "id": uuid,
             "game_id": game.id,
             "game_name": game.name,
             "timestamp": Utc::now().to_rfc3339(),
-            "dat_file": dat_file_name,
-            "dat_path": changed_file_path.to_string_lossy().to_string(),
+            "save_mode": plan.save_mode,
+            "kind": plan.kind,
+            "files": file_entries,
+            "thumbnail": thumbnail_file,
         });
+        if let Some(obj) = metadata.as_object_mut() {
+            obj.extend(plan.metadata);
+            if !renpy_saves.is_empty() {
+                obj.insert("renpy_saves".to_string(), serde_json::json!(renpy_saves));
+            }
+            if !rpgmaker_saves.is_empty() {
+                obj.insert("rpgmaker_saves".to_string(), serde_json::json!(rpgmaker_saves));
+            }
+        }
         let metadata_path = snapshot_folder.join("metadata.json");
         fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
 
@@ -143,29 +1377,84 @@ impl SnapshotManager {
         fs::write(&context_path, "").ok();
 
         let note_path = snapshot_folder.join("note.txt");
-        fs::write(&note_path, "").ok();
+        fs::write(&note_path, plan.note.as_deref().unwrap_or("")).ok();
 
         let snapshot = Snapshot {
             id: uuid,
-            game_id: game.id,
-            name: default_name,
-            original_save_path: changed_file_path.to_string_lossy().to_string(),
+            game_id: game.id.clone(),
+            name: name.unwrap_or(default_name),
+            original_save_path: plan.original_save_path,
             backup_save_path: snapshot_folder.to_string_lossy().to_string(),
-            note: None,
+            note: plan.note,
             created_at: Utc::now().to_rfc3339(),
+            thumbnail_path: thumbnail_file
+                .map(|f| snapshot_folder.join(f).to_string_lossy().to_string()),
+            kind: Some(plan.kind.to_string()),
+            summary: summary.map(|(_, description)| description),
         };
 
         self.db.add_snapshot(&snapshot)?;
-        
-        {
-            match last_snapshot_time.lock() {
-                Ok(mut last_time) => *last_time = Instant::now(),
-                Err(_) => {},
-            }
+        self.db.add_snapshot_files(&snapshot.id, &file_records)?;
+
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vn-save-manager-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_group_game(save_root: &Path) -> Game {
+        Game {
+            id: "game".to_string(),
+            name: "game".to_string(),
+            exe_path: None,
+            game_folder_path: save_root.to_string_lossy().to_string(),
+            save_folder_path: Some(save_root.to_string_lossy().to_string()),
+            cover_image: None,
+            save_mode: Some("file_group".to_string()),
+            save_config: Some(r#"{"version": 1, "extensions": [], "group_by_prefix": true}"#.to_string()),
+            paused: false,
         }
-        
-        self.app_handle.emit("snapshot-created", &snapshot)?;
+    }
 
-        Ok(())
+    #[test]
+    fn file_group_slots_changed_together_get_their_own_snapshots() {
+        let root = temp_dir();
+        for name in ["save_001.dat", "save_001.png", "save_002.dat", "save_002.png", "save_003.dat"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+        let game = file_group_game(&root);
+
+        let changed = [root.join("save_001.dat"), root.join("save_002.png"), root.join("save_002.dat")];
+        let plans = SnapshotManager::plan_file_group(&game, &changed).unwrap();
+
+        assert_eq!(plans.len(), 2);
+        for (plan, slot) in plans.iter().zip(["save_001", "save_002"]) {
+            let relative_paths: Vec<&str> = plan.files.iter().map(|f| f.relative_path.as_str()).collect();
+            assert_eq!(relative_paths, vec![format!("{}.dat", slot), format!("{}.png", slot)]);
+            assert_eq!(plan.thumbnail_file, Some(format!("{}/{}.png", SAVE_BACKUP_DIR, slot)));
+            assert!(plan.original_save_path.ends_with(&format!("{}.dat", slot)));
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn file_group_ignores_slots_with_only_images() {
+        let root = temp_dir();
+        fs::write(root.join("save_004.png"), "png").unwrap();
+        let game = file_group_game(&root);
+
+        let plans = SnapshotManager::plan_file_group(&game, &[root.join("save_004.png")]).unwrap();
+        assert!(plans.is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
     }
 }
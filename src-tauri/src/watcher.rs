@@ -1,33 +1,272 @@
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
-use std::sync::mpsc::{channel, Receiver};
-use std::time::Duration;
+use notify::{Config, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::AppHandle;
 use tauri::Emitter;
 
+/// PollWatcher 扫描存档目录的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 监视存档目录所用的方式，对应 save_config 中的 `watch_backend`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchBackend {
+    /// 系统文件通知（notify 的 RecommendedWatcher）
+    Native,
+    /// 定时扫描，适用于网络共享、部分 FUSE 挂载等收不到通知的位置
+    Poll,
+}
+
+impl WatchBackend {
+    pub fn is_native(&self) -> bool {
+        *self == WatchBackend::Native
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WatchBackend::Native => "native",
+            WatchBackend::Poll => "poll",
+        }
+    }
+}
+
+/// 单个游戏的监视状态
+struct WatchEntry {
+    path: PathBuf,
+    backend: WatchBackend,
+    watching: bool,
+    last_event: Option<String>,
+    last_error: Option<String>,
+}
+
+/// get_watch_status 返回给前端的监视状态
+#[derive(Debug, Serialize)]
+pub struct WatchStatus {
+    pub game_id: String,
+    pub path: String,
+    pub watching: bool,
+    pub backend: String,  // native, poll
+    pub last_event: Option<String>,
+    pub last_error: Option<String>,
+}
+
 pub struct SaveWatcher {
     watcher: RecommendedWatcher,
+    /// 第一个使用轮询的游戏被添加时才创建
+    poll_watcher: Option<PollWatcher>,
+    tx: Sender<notify::Result<Event>>,
+    /// 以游戏 id 为键
+    entries: HashMap<String, WatchEntry>,
 }
 
 impl SaveWatcher {
     pub fn new(_app_handle: AppHandle) -> (Self, Receiver<notify::Result<Event>>) {
         let (tx, rx) = channel();
         
-        let watcher = RecommendedWatcher::new(tx, Config::default()).expect("Failed to create watcher");
+        let watcher = RecommendedWatcher::new(tx.clone(), Config::default()).expect("Failed to create watcher");
+
+        (Self { watcher, poll_watcher: None, tx, entries: HashMap::new() }, rx)
+    }
+
+    /// 开始监视游戏的存档目录；已在监视的游戏会先取消原有的监视。
+    /// 失败时同样记录到监视状态中
+    pub fn watch(&mut self, game_id: &str, path: &str, backend: WatchBackend) -> notify::Result<()> {
+        if self.entries.contains_key(game_id) {
+            let _ = self.unwatch(game_id);
+        }
+
+        let result = self.add_watch(Path::new(path), backend);
+        self.entries.insert(game_id.to_string(), WatchEntry {
+            path: PathBuf::from(path),
+            backend,
+            watching: result.is_ok(),
+            last_event: None,
+            last_error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    }
+
+    fn add_watch(&mut self, path: &Path, backend: WatchBackend) -> notify::Result<()> {
+        match backend {
+            WatchBackend::Native => self.watcher.watch(path, RecursiveMode::Recursive),
+            WatchBackend::Poll => {
+                if self.poll_watcher.is_none() {
+                    let config = Config::default().with_poll_interval(POLL_INTERVAL);
+                    self.poll_watcher = Some(PollWatcher::new(self.tx.clone(), config)?);
+                }
+                match self.poll_watcher.as_mut() {
+                    Some(poll_watcher) => poll_watcher.watch(path, RecursiveMode::Recursive),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    pub fn unwatch(&mut self, game_id: &str) -> notify::Result<()> {
+        let entry = match self.entries.remove(game_id) {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+        if !entry.watching {
+            return Ok(());
+        }
+
+        // 其他游戏仍以同样方式监视同一目录时保留监视
+        let shared = self.entries.values().any(|e| e.watching && e.path == entry.path && e.backend == entry.backend);
+        if shared {
+            return Ok(());
+        }
+
+        match entry.backend {
+            WatchBackend::Native => self.watcher.unwatch(&entry.path),
+            WatchBackend::Poll => match self.poll_watcher.as_mut() {
+                Some(poll_watcher) => poll_watcher.unwatch(&entry.path),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// 记录收到事件的时间（所有监视目录包含该路径的游戏）
+    pub fn record_event(&mut self, path: &Path) {
+        let now = chrono::Utc::now().to_rfc3339();
+        for entry in self.entries.values_mut().filter(|e| path.starts_with(&e.path)) {
+            entry.last_event = Some(now.clone());
+        }
+    }
 
-        (Self { watcher }, rx)
+    /// 记录与某个游戏相关的错误（如存档配置无效）
+    pub fn record_game_error(&mut self, game_id: &str, message: String) {
+        if let Some(entry) = self.entries.get_mut(game_id) {
+            entry.last_error = Some(message);
+        }
+    }
+
+    /// 记录 notify 报告的错误；错误没有附带路径时记录到所有游戏
+    pub fn record_error(&mut self, error: &notify::Error) {
+        let message = error.to_string();
+        for entry in self.entries.values_mut() {
+            if error.paths.is_empty() || error.paths.iter().any(|p| p.starts_with(&entry.path)) {
+                entry.last_error = Some(message.clone());
+            }
+        }
     }
 
-    pub fn watch(&mut self, path: &str) -> notify::Result<()> {
-        let path = Path::new(path);
-        self.watcher.watch(path, RecursiveMode::Recursive)?;
-        Ok(())
+    pub fn status(&self) -> Vec<WatchStatus> {
+        self.entries
+            .iter()
+            .map(|(game_id, entry)| WatchStatus {
+                game_id: game_id.clone(),
+                path: entry.path.to_string_lossy().to_string(),
+                watching: entry.watching,
+                backend: entry.backend.as_str().to_string(),
+                last_event: entry.last_event.clone(),
+                last_error: entry.last_error.clone(),
+            })
+            .collect()
+    }
+}
+
+/// 记录应用自身（如恢复快照）写入的路径，watcher 在有效期内忽略这些路径上的事件
+#[derive(Clone, Default)]
+pub struct WriteSuppressor {
+    paths: Arc<Mutex<HashMap<PathBuf, Instant>>>,
+}
+
+impl WriteSuppressor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在 `duration` 内忽略这些路径；重复登记会延长有效期
+    pub fn suppress<I: IntoIterator<Item = PathBuf>>(&self, paths: I, duration: Duration) {
+        if let Ok(mut map) = self.paths.lock() {
+            let until = Instant::now() + duration;
+            for path in paths {
+                map.insert(path, until);
+            }
+        }
+    }
+
+    pub fn is_suppressed(&self, path: &Path) -> bool {
+        match self.paths.lock() {
+            Ok(mut map) => {
+                let now = Instant::now();
+                map.retain(|_, until| *until > now);
+                map.contains_key(path)
+            },
+            Err(_) => false,
+        }
+    }
+}
+
+struct PendingBatch {
+    paths: Vec<PathBuf>,
+    first_event: Instant,
+    last_event: Instant,
+}
+
+/// 按游戏聚合存档变化：游戏安静 `quiet` 时长后（或最多等待 `max_wait`）一次性交出所有变化的路径
+pub struct EventBatcher {
+    pending: HashMap<String, PendingBatch>,
+    quiet: Duration,
+    max_wait: Duration,
+}
+
+impl EventBatcher {
+    pub fn new(quiet: Duration, max_wait: Duration) -> Self {
+        Self {
+            pending: HashMap::new(),
+            quiet,
+            max_wait,
+        }
+    }
+
+    pub fn add(&mut self, game_id: String, path: PathBuf) {
+        let now = Instant::now();
+        let batch = self.pending.entry(game_id).or_insert_with(|| PendingBatch {
+            paths: Vec::new(),
+            first_event: now,
+            last_event: now,
+        });
+        batch.last_event = now;
+        if !batch.paths.contains(&path) {
+            batch.paths.push(path);
+        }
+    }
+
+    fn is_ready(&self, batch: &PendingBatch) -> bool {
+        batch.last_event.elapsed() >= self.quiet || batch.first_event.elapsed() >= self.max_wait
+    }
+
+    /// 取出所有已经可以处理的批次
+    pub fn take_ready(&mut self) -> Vec<(String, Vec<PathBuf>)> {
+        let ready: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, batch)| self.is_ready(batch))
+            .map(|(game_id, _)| game_id.clone())
+            .collect();
+
+        ready
+            .into_iter()
+            .filter_map(|game_id| self.pending.remove(&game_id).map(|batch| (game_id, batch.paths)))
+            .collect()
     }
 
-    pub fn unwatch(&mut self, path: &str) -> notify::Result<()> {
-        let path = Path::new(path);
-        self.watcher.unwatch(path)?;
-        Ok(())
+    /// 距离下一个批次可以处理的等待时间，没有待处理批次时返回 None
+    pub fn next_timeout(&self) -> Option<Duration> {
+        self.pending
+            .values()
+            .map(|batch| {
+                let until_quiet = self.quiet.saturating_sub(batch.last_event.elapsed());
+                let until_max = self.max_wait.saturating_sub(batch.first_event.elapsed());
+                until_quiet.min(until_max)
+            })
+            .min()
     }
 }
 
@@ -1,7 +1,7 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { Game, Snapshot, Screenshot } from "./types";
+import { Game, Snapshot, Screenshot, SnapshotFailure } from "./types";
 import { open } from "@tauri-apps/plugin-dialog";
 import "./App.css";
 import AddGameModal from "./components/AddGameModal";
@@ -12,6 +12,7 @@ import SnapshotDetail from "./components/SnapshotDetail";
 import ScreenshotList from "./components/ScreenshotList";
 import ScreenshotDetail from "./components/ScreenshotDetail";
 import ConfirmDialog from "./components/ConfirmDialog";
+import Toast from "./components/Toast";
 
 function App() {
   const [showWelcome, setShowWelcome] = useState(true);
@@ -40,10 +41,12 @@ function App() {
     onDeleteVisualLoggerChange?: (value: boolean) => void;
   }>({ show: false, title: "", message: "", onConfirm: () => {} });
   const [deleteVisualLogger, setDeleteVisualLogger] = useState(false);
+  const [toast, setToast] = useState<{ message: string; type: "success" | "error" | "info" } | null>(null);
 
   useEffect(() => {
     let unlistenSnapshot: (() => void) | undefined;
     let unlistenScreenshot: (() => void) | undefined;
+    let unlistenSnapshotFailed: (() => void) | undefined;
 
     listen<Snapshot>("snapshot-created", (event) => {
       if (selectedGame && event.payload.game_id === selectedGame.id) {
@@ -67,11 +70,25 @@ function App() {
       unlistenScreenshot = fn;
     }).catch(() => {});
 
+    listen<SnapshotFailure>("snapshot-failed", (event) => {
+      // 快捷键快照在没有运行中的游戏时不带 game_id
+      if (!event.payload.game_id) {
+        setToast({ message: `快照失败: ${event.payload.error}`, type: "error" });
+        return;
+      }
+      const game = games.find(g => g.id === event.payload.game_id);
+      const gameName = game ? game.name : event.payload.game_id;
+      setToast({ message: `游戏 "${gameName}" 快照失败: ${event.payload.error}`, type: "error" });
+    }).then((fn) => {
+      unlistenSnapshotFailed = fn;
+    }).catch(() => {});
+
     return () => {
       if (unlistenSnapshot) unlistenSnapshot();
       if (unlistenScreenshot) unlistenScreenshot();
+      if (unlistenSnapshotFailed) unlistenSnapshotFailed();
     };
-  }, [selectedGame]);
+  }, [selectedGame, games]);
 
   function handleStart() {
     setShowWelcome(false);
@@ -406,6 +423,14 @@ function App() {
 
   return (
     <div className="flex h-screen w-screen bg-gray-50">
+      {toast && (
+        <Toast
+          message={toast.message}
+          type={toast.type}
+          duration={5000}
+          onClose={() => setToast(null)}
+        />
+      )}
       <GameList
         games={games}
         selectedGame={selectedGame}
@@ -413,6 +438,7 @@ function App() {
         onDeleteGame={handleDeleteGame}
         onEditGame={handleEditGame}
         onAddGame={handleAddGameClick}
+        onPauseChanged={loadGames}
       />
 
       {selectedGame ? (
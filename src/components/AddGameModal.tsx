@@ -1,4 +1,9 @@
-import { useState } from "react";
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { SaveConfigPreview, DetectedEngine } from "../types";
+import { buildSaveConfig, modeConfigOf } from "../saveConfig";
+import SaveRulesFields from "./SaveRulesFields";
+import PollingField from "./PollingField";
 
 interface AddGameModalProps {
   show: boolean;
@@ -25,33 +30,88 @@ export default function AddGameModal({
 }: AddGameModalProps) {
   const [saveMode, setSaveMode] = useState<string>("single_file");
   const [extensions, setExtensions] = useState<string>("dat");
+  const [usePolling, setUsePolling] = useState<boolean>(false);
+  const [includeGlobs, setIncludeGlobs] = useState<string>("");
+  const [excludeGlobs, setExcludeGlobs] = useState<string>("");
+  const [preview, setPreview] = useState<SaveConfigPreview | null>(null);
+  const [previewError, setPreviewError] = useState<string>("");
+  const [detectedEngine, setDetectedEngine] = useState<DetectedEngine | null>(null);
+
+  // 选择 exe 后根据游戏目录识别引擎，用预设填写存档模式、规则和存档位置
+  useEffect(() => {
+    setDetectedEngine(null);
+    if (!exePath) return;
+    invoke<DetectedEngine | null>("detect_engine", { exePath })
+      .then((engine) => {
+        if (!engine) return;
+        setDetectedEngine(engine);
+        const config = JSON.parse(engine.save_config);
+        setSaveMode(engine.save_mode);
+        if (Array.isArray(config.extensions)) {
+          setExtensions(config.extensions.join(", "));
+        }
+        setIncludeGlobs((config.include_globs || []).join(", "));
+        setExcludeGlobs((config.exclude_globs || []).join(", "));
+        if (!savePath && engine.save_folder_path) {
+          onSavePathChange(engine.save_folder_path);
+        }
+      })
+      .catch(() => {});
+  }, [exePath]);
 
   if (!show) return null;
 
-  function getSaveConfig(): string {
+  function getModeConfig(): Record<string, unknown> {
+    // 引擎预设中该模式专有的配置（如 kdt/ksd 扩展名）
+    if (detectedEngine && detectedEngine.save_mode === saveMode && saveMode !== "single_file") {
+      return modeConfigOf(detectedEngine.save_config);
+    }
     switch (saveMode) {
       case "single_file":
         const exts = extensions.split(",").map(e => e.trim()).filter(e => e.length > 0);
-        return JSON.stringify({ extensions: exts.length > 0 ? exts : ["dat"] });
+        return { extensions: exts.length > 0 ? exts : ["dat"] };
       case "folder":
-        return JSON.stringify({ 
+        return { 
           folder_name: null,
           include_extensions: [],
           exclude_extensions: []
-        });
+        };
       case "file_group":
-        return JSON.stringify({ 
+        return { 
           extensions: [],
           pattern: null,
           group_by_prefix: true
-        });
+        };
       case "container":
-        return JSON.stringify({ 
+        return { 
           container_extensions: [],
           inner_extensions: []
-        });
+        };
       default:
-        return JSON.stringify({ extensions: ["dat"] });
+        return { extensions: ["dat"] };
+    }
+  }
+
+  function getSaveConfig(): string {
+    return buildSaveConfig(getModeConfig(), includeGlobs, excludeGlobs, usePolling);
+  }
+
+  async function handlePreview() {
+    if (!savePath) {
+      alert("请先选择存档文件夹");
+      return;
+    }
+    setPreview(null);
+    setPreviewError("");
+    try {
+      const result = await invoke<SaveConfigPreview>("preview_save_config", {
+        saveFolderPath: savePath,
+        saveMode,
+        saveConfig: getSaveConfig(),
+      });
+      setPreview(result);
+    } catch (error) {
+      setPreviewError(String(error));
     }
   }
 
@@ -153,6 +213,11 @@ export default function AddGameModal({
               <option value="file_group">文件组（新增模式）</option>
               <option value="container">容器文件</option>
             </select>
+            {detectedEngine && (
+              <p className="text-xs text-blue-600 mt-1">
+                检测到 {detectedEngine.name}（{detectedEngine.evidence}），已按预设填写存档模式和规则
+              </p>
+            )}
             <p className="text-xs text-gray-500 mt-1">
               {saveMode === "single_file" && "每次存档覆盖同一个文件（如 save.dat）"}
               {saveMode === "folder" && "存档是一个文件夹，包含多个文件（如 savedata/ 文件夹）"}
@@ -187,6 +252,46 @@ export default function AddGameModal({
             </div>
           )}
           
+          <SaveRulesFields
+            includeGlobs={includeGlobs}
+            excludeGlobs={excludeGlobs}
+            onIncludeGlobsChange={setIncludeGlobs}
+            onExcludeGlobsChange={setExcludeGlobs}
+          />
+
+          <PollingField usePolling={usePolling} onUsePollingChange={setUsePolling} />
+
+          <div>
+            <button
+              type="button"
+              onClick={handlePreview}
+              className="px-4 py-2 bg-gray-100 hover:bg-gray-200 text-gray-700 text-sm font-medium rounded-xl transition-colors"
+            >
+              预览会被快照的文件
+            </button>
+            {previewError && (
+              <p className="text-xs text-red-600 mt-2">{previewError}</p>
+            )}
+            {preview && (
+              <div className="mt-2">
+                <p className="text-xs text-gray-600 mb-1">
+                  {preview.captured_count} 个文件会被快照（{preview.group_count} 组），{preview.ignored_count} 个文件被忽略
+                  {preview.truncated && "，文件过多，仅显示部分"}
+                </p>
+                <ul className="max-h-48 overflow-y-auto border border-gray-200 rounded-lg divide-y divide-gray-100 text-xs">
+                  {preview.items.map((item) => (
+                    <li key={item.relative_path} className="px-3 py-1.5">
+                      <span className={item.group ? "text-gray-900" : "text-gray-400"}>{item.relative_path}</span>
+                      <span className="ml-2 text-gray-500">
+                        {item.group ? `→ ${item.group}` : item.reason}
+                      </span>
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            )}
+          </div>
+
           <p className="text-xs text-gray-500 leading-relaxed">
             提示：请选择游戏的存档文件夹和exe文件。软件会监控存档文件的变化，并针对该exe所在窗口生成快照。
           </p>
@@ -1,5 +1,9 @@
 import { useState, useEffect } from "react";
-import { Game } from "../types";
+import { invoke } from "@tauri-apps/api/core";
+import { Game, WatchStatus, ObserveStatus, ConfigSuggestion } from "../types";
+import { buildSaveConfig, modeConfigOf } from "../saveConfig";
+import SaveRulesFields from "./SaveRulesFields";
+import PollingField from "./PollingField";
 
 interface EditGameModalProps {
   show: boolean;
@@ -28,54 +32,144 @@ export default function EditGameModal({
 }: EditGameModalProps) {
   const [saveMode, setSaveMode] = useState<string>("single_file");
   const [extensions, setExtensions] = useState<string>("dat");
+  const [usePolling, setUsePolling] = useState<boolean>(false);
+  const [includeGlobs, setIncludeGlobs] = useState<string>("");
+  const [excludeGlobs, setExcludeGlobs] = useState<string>("");
+  const [watchStatus, setWatchStatus] = useState<WatchStatus | null>(null);
+  const [observeStatus, setObserveStatus] = useState<ObserveStatus | null>(null);
+  const [suggestion, setSuggestion] = useState<ConfigSuggestion | null>(null);
+  // 该模式专有的配置（如引擎预设的扩展名、folder_name）以游戏已保存的配置或应用的观察建议为准；
+  // 只有切换到其他模式时才使用默认值
+  const [baseModeConfig, setBaseModeConfig] = useState<{ mode: string; config: Record<string, unknown> } | null>(null);
 
   useEffect(() => {
     if (game) {
       const mode = game.save_mode || "single_file";
       setSaveMode(mode);
-      
-      if (mode === "single_file" && game.save_config) {
+
+      let polling = false;
+      let include: string[] = [];
+      let exclude: string[] = [];
+      if (game.save_config) {
         try {
           const config = JSON.parse(game.save_config);
-          if (config.extensions && Array.isArray(config.extensions)) {
-            setExtensions(config.extensions.join(", "));
-          }
+          polling = config.watch_backend === "poll";
+          if (Array.isArray(config.include_globs)) include = config.include_globs;
+          if (Array.isArray(config.exclude_globs)) exclude = config.exclude_globs;
         } catch (e) {
           // 解析失败，使用默认值
         }
       }
+      setUsePolling(polling);
+      setIncludeGlobs(include.join(", "));
+      setExcludeGlobs(exclude.join(", "));
+
+      setSuggestion(null);
+      setObserveStatus(null);
+      invoke<ObserveStatus | null>("get_observe_status", { gameId: game.id })
+        .then(setObserveStatus)
+        .catch(() => {});
+
+      setWatchStatus(null);
+      invoke<WatchStatus[]>("get_watch_status")
+        .then((statuses) => setWatchStatus(statuses.find(s => s.game_id === game.id) || null))
+        .catch(() => {});
+      
+      const modeConfig = modeConfigOf(game.save_config);
+      setBaseModeConfig({ mode, config: modeConfig });
+      setExtensions(
+        mode === "single_file" && Array.isArray(modeConfig.extensions) ? modeConfig.extensions.join(", ") : "dat"
+      );
     }
   }, [game]);
 
+  // 观察期间定时刷新已记录的文件
+  useEffect(() => {
+    if (!game || !observeStatus) return;
+    const timer = setInterval(() => {
+      invoke<ObserveStatus | null>("get_observe_status", { gameId: game.id })
+        .then(setObserveStatus)
+        .catch(() => {});
+    }, 2000);
+    return () => clearInterval(timer);
+  }, [game, observeStatus !== null]);
+
   if (!show || !game) return null;
 
-  function getSaveConfig(): string {
+  async function handleStartObserving() {
+    if (!game) return;
+    try {
+      await invoke("start_observing", { gameId: game.id });
+      setSuggestion(null);
+      setObserveStatus(await invoke<ObserveStatus | null>("get_observe_status", { gameId: game.id }));
+    } catch (error) {
+      alert(`开始观察失败: ${error}`);
+    }
+  }
+
+  async function handleStopObserving() {
+    if (!game) return;
+    try {
+      setSuggestion(await invoke<ConfigSuggestion>("stop_observing", { gameId: game.id }));
+    } catch (error) {
+      alert(`${error}`);
+    }
+    setObserveStatus(null);
+  }
+
+  function applySuggestion(result: ConfigSuggestion) {
+    const config = JSON.parse(result.save_config);
+    const includeList: string[] = config.include_globs || [];
+    const excludeList: string[] = config.exclude_globs || [];
+    const modeConfig = modeConfigOf(result.save_config);
+    setSaveMode(result.save_mode);
+    setBaseModeConfig({ mode: result.save_mode, config: modeConfig });
+    if (result.save_mode === "single_file" && Array.isArray(modeConfig.extensions)) {
+      setExtensions(modeConfig.extensions.join(", "));
+    }
+    setIncludeGlobs(includeList.join(", "));
+    setExcludeGlobs(excludeList.join(", "));
+    setSuggestion(null);
+  }
+
+  function getModeConfig(): Record<string, unknown> {
+    const exts = extensions.split(",").map(e => e.trim()).filter(e => e.length > 0);
+    const editedExtensions = exts.length > 0 ? exts : ["dat"];
+    // 模式未变时保留表单中没有展示的字段，只替换用户编辑过的扩展名
+    if (baseModeConfig && baseModeConfig.mode === saveMode) {
+      return saveMode === "single_file"
+        ? { ...baseModeConfig.config, extensions: editedExtensions }
+        : baseModeConfig.config;
+    }
     switch (saveMode) {
       case "single_file":
-        const exts = extensions.split(",").map(e => e.trim()).filter(e => e.length > 0);
-        return JSON.stringify({ extensions: exts.length > 0 ? exts : ["dat"] });
+        return { extensions: editedExtensions };
       case "folder":
-        return JSON.stringify({ 
+        return { 
           folder_name: null,
           include_extensions: [],
           exclude_extensions: []
-        });
+        };
       case "file_group":
-        return JSON.stringify({ 
+        return { 
           extensions: [],
           pattern: null,
           group_by_prefix: true
-        });
+        };
       case "container":
-        return JSON.stringify({ 
+        return { 
           container_extensions: [],
           inner_extensions: []
-        });
+        };
       default:
-        return JSON.stringify({ extensions: ["dat"] });
+        return { extensions: ["dat"] };
     }
   }
 
+  function getSaveConfig(): string {
+    return buildSaveConfig(getModeConfig(), includeGlobs, excludeGlobs, usePolling);
+  }
+
   return (
     <div className="fixed inset-0 bg-black/40 backdrop-blur-sm flex items-center justify-center z-50">
       <div className="bg-white rounded-2xl shadow-2xl w-full max-w-md mx-4 overflow-hidden">
@@ -205,6 +299,70 @@ export default function EditGameModal({
             </div>
           )}
           
+          <SaveRulesFields
+            includeGlobs={includeGlobs}
+            excludeGlobs={excludeGlobs}
+            onIncludeGlobsChange={setIncludeGlobs}
+            onExcludeGlobsChange={setExcludeGlobs}
+          />
+
+          <PollingField
+            usePolling={usePolling}
+            onUsePollingChange={setUsePolling}
+            watchStatus={watchStatus}
+          />
+
+          <div>
+            <label className="block text-sm font-medium text-gray-700 mb-2">
+              观察模式
+            </label>
+            {observeStatus ? (
+              <div className="flex items-center gap-2">
+                <span className="text-xs text-gray-600 flex-1">
+                  {observeStatus.expired
+                    ? `观察已超时，自动快照已恢复。已记录 ${observeStatus.files.length} 个文件写入，可以结束并生成建议`
+                    : `正在观察，已记录 ${observeStatus.files.length} 个文件写入。请在游戏中存档几次后结束`}
+                </span>
+                <button
+                  type="button"
+                  onClick={handleStopObserving}
+                  className="px-4 py-2 bg-gray-100 hover:bg-gray-200 text-gray-700 text-sm font-medium rounded-xl transition-colors whitespace-nowrap"
+                >
+                  结束并生成建议
+                </button>
+              </div>
+            ) : (
+              <button
+                type="button"
+                onClick={handleStartObserving}
+                className="px-4 py-2 bg-gray-100 hover:bg-gray-200 text-gray-700 text-sm font-medium rounded-xl transition-colors"
+              >
+                开始观察
+              </button>
+            )}
+            <p className="text-xs text-gray-500 mt-1">
+              观察期间只记录游戏写入了哪些文件，不创建快照，最长 30 分钟；结束后根据记录推荐存档模式和规则
+            </p>
+            {suggestion && (
+              <div className="mt-2 bg-gray-50 border border-gray-200 rounded-lg p-3 text-xs text-gray-700 space-y-1">
+                <p>建议存档模式: <strong>{suggestion.save_mode}</strong></p>
+                <p className="font-mono break-all text-gray-500">{suggestion.save_config}</p>
+                <ul className="list-disc list-inside text-gray-600">
+                  {suggestion.notes.map((note) => (
+                    <li key={note}>{note}</li>
+                  ))}
+                </ul>
+                <button
+                  type="button"
+                  onClick={() => applySuggestion(suggestion)}
+                  className="mt-1 px-3 py-1.5 bg-blue-500 hover:bg-blue-600 text-white font-medium rounded-lg transition-colors"
+                >
+                  应用建议
+                </button>
+              </div>
+            )}
+          </div>
+
           <p className="text-xs text-gray-500 leading-relaxed">
             提示：如果路径字段留空，则不会修改该路径。修改路径后需要确保新路径存在且有效。
           </p>
@@ -9,6 +9,7 @@ interface GameListProps {
   onDeleteGame: (game: Game) => void;
   onEditGame: (game: Game) => void;
   onAddGame: () => void;
+  onPauseChanged: () => void;
 }
 
 interface GameStats {
@@ -23,8 +24,52 @@ export default function GameList({
   onDeleteGame,
   onEditGame,
   onAddGame,
+  onPauseChanged,
 }: GameListProps) {
   const [gameStats, setGameStats] = useState<Record<string, GameStats>>({});
+  const [watchingPaused, setWatchingPaused] = useState(false);
+  const [observingGames, setObservingGames] = useState<string[]>([]);
+
+  useEffect(() => {
+    invoke<boolean>("get_watching_paused")
+      .then(setWatchingPaused)
+      .catch(() => {});
+  }, []);
+
+  // 观察在编辑窗口中开始，超时后自动结束，定时刷新
+  useEffect(() => {
+    function loadObserving() {
+      invoke<string[]>("get_observing_games")
+        .then(setObservingGames)
+        .catch(() => {});
+    }
+    loadObserving();
+    const timer = setInterval(loadObserving, 5000);
+    return () => clearInterval(timer);
+  }, [games]);
+
+  async function toggleGamePaused(game: Game) {
+    const paused = !game.paused;
+    const reconcile = !paused && confirm(`是否为 "${game.name}" 暂停期间发生变化的存档创建快照？`);
+    try {
+      await invoke("set_game_paused", { gameId: game.id, paused, reconcile });
+      onPauseChanged();
+    } catch (e) {
+      alert(`操作失败: ${e}`);
+    }
+  }
+
+  async function toggleWatchingPaused() {
+    const paused = !watchingPaused;
+    const reconcile = !paused && confirm("是否为暂停期间发生变化的存档创建快照？");
+    try {
+      await invoke("set_watching_paused", { paused, reconcile });
+      setWatchingPaused(paused);
+      onPauseChanged();
+    } catch (e) {
+      alert(`操作失败: ${e}`);
+    }
+  }
 
   useEffect(() => {
     async function loadStats() {
@@ -49,7 +94,32 @@ export default function GameList({
   return (
     <div className="w-64 bg-white border-r border-gray-200 flex flex-col h-screen">
       <div className="p-6 border-b border-gray-100">
-        <h2 className="text-2xl font-semibold text-gray-900">我的游戏</h2>
+        <div className="flex items-center justify-between">
+          <h2 className="text-2xl font-semibold text-gray-900">我的游戏</h2>
+          <button
+            className={`p-1.5 rounded-lg transition-colors ${
+              watchingPaused
+                ? "text-amber-500 bg-amber-50 hover:bg-amber-100"
+                : "text-gray-400 hover:text-gray-600 hover:bg-gray-100"
+            }`}
+            onClick={toggleWatchingPaused}
+            title={watchingPaused ? "恢复所有游戏的自动快照" : "暂停所有游戏的自动快照"}
+          >
+            {watchingPaused ? (
+              <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M14.752 11.168l-3.197-2.132A1 1 0 0010 9.87v4.263a1 1 0 001.555.832l3.197-2.132a1 1 0 000-1.664z" />
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M21 12a9 9 0 11-18 0 9 9 0 0118 0z" />
+              </svg>
+            ) : (
+              <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M10 9v6m4-6v6m7-3a9 9 0 11-18 0 9 9 0 0118 0z" />
+              </svg>
+            )}
+          </button>
+        </div>
+        {watchingPaused && (
+          <p className="text-xs text-amber-600 mt-2">已暂停所有游戏的自动快照</p>
+        )}
       </div>
       
       <ul className="flex-1 overflow-y-auto p-3 space-y-1.5">
@@ -72,6 +142,25 @@ export default function GameList({
                   {g.name}
                 </span>
                 <div className="flex items-center gap-1 opacity-0 group-hover:opacity-100 transition-opacity">
+                  <button
+                    className="p-1.5 rounded-lg text-gray-400 hover:text-amber-500 hover:bg-amber-50"
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      toggleGamePaused(g);
+                    }}
+                    title={g.paused ? "恢复自动快照" : "暂停自动快照"}
+                  >
+                    {g.paused ? (
+                      <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M14.752 11.168l-3.197-2.132A1 1 0 0010 9.87v4.263a1 1 0 001.555.832l3.197-2.132a1 1 0 000-1.664z" />
+                        <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M21 12a9 9 0 11-18 0 9 9 0 0118 0z" />
+                      </svg>
+                    ) : (
+                      <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M10 9v6m4-6v6m7-3a9 9 0 11-18 0 9 9 0 0118 0z" />
+                      </svg>
+                    )}
+                  </button>
                   <button
                     className="p-1.5 rounded-lg text-gray-400 hover:text-blue-500 hover:bg-blue-50"
                     onClick={(e) => {
@@ -102,6 +191,18 @@ export default function GameList({
                 <span>{stats.snapshotCount} 个快照</span>
                 <span>•</span>
                 <span>{stats.screenshotCount} 张截图</span>
+                {g.paused && (
+                  <>
+                    <span>•</span>
+                    <span className="text-amber-600">已暂停</span>
+                  </>
+                )}
+                {observingGames.includes(g.id) && (
+                  <>
+                    <span>•</span>
+                    <span className="text-blue-600">观察中</span>
+                  </>
+                )}
               </div>
             </li>
           );
@@ -0,0 +1,38 @@
+import { WatchStatus } from "../types";
+
+interface PollingFieldProps {
+  usePolling: boolean;
+  onUsePollingChange: (value: boolean) => void;
+  // 已添加的游戏显示当前的监视状态
+  watchStatus?: WatchStatus | null;
+}
+
+export default function PollingField({
+  usePolling,
+  onUsePollingChange,
+  watchStatus,
+}: PollingFieldProps) {
+  return (
+    <div>
+      <label className="flex items-center gap-2 text-sm font-medium text-gray-700">
+        <input
+          type="checkbox"
+          checked={usePolling}
+          onChange={(e) => onUsePollingChange(e.target.checked)}
+          className="rounded border-gray-300 text-blue-500 focus:ring-blue-500"
+        />
+        使用轮询监视存档
+      </label>
+      <p className="text-xs text-gray-500 mt-1">
+        存档位于网络共享或虚拟磁盘、无法自动检测到存档时启用（定时扫描存档文件夹）
+      </p>
+      {watchStatus && (
+        <p className={`text-xs mt-1 ${watchStatus.watching && !watchStatus.last_error ? "text-gray-500" : "text-red-600"}`}>
+          当前: {watchStatus.watching ? `正在监视（${watchStatus.backend === "poll" ? "轮询" : "系统通知"}）` : "未在监视"}
+          {watchStatus.last_event && `，最近事件 ${new Date(watchStatus.last_event).toLocaleString()}`}
+          {watchStatus.last_error && `，错误: ${watchStatus.last_error}`}
+        </p>
+      )}
+    </div>
+  );
+}
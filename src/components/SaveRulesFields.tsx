@@ -0,0 +1,41 @@
+interface SaveRulesFieldsProps {
+  includeGlobs: string;
+  excludeGlobs: string;
+  onIncludeGlobsChange: (value: string) => void;
+  onExcludeGlobsChange: (value: string) => void;
+}
+
+export default function SaveRulesFields({
+  includeGlobs,
+  excludeGlobs,
+  onIncludeGlobsChange,
+  onExcludeGlobsChange,
+}: SaveRulesFieldsProps) {
+  return (
+    <div>
+      <label className="block text-sm font-medium text-gray-700 mb-2">
+        包含规则
+      </label>
+      <input
+        type="text"
+        placeholder="save/*.dat"
+        value={includeGlobs}
+        onChange={(e) => onIncludeGlobsChange(e.target.value)}
+        className="w-full px-4 py-3 bg-gray-50 border border-gray-200 rounded-xl text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+      />
+      <label className="block text-sm font-medium text-gray-700 mt-3 mb-2">
+        排除规则
+      </label>
+      <input
+        type="text"
+        placeholder="system.dat, *.tmp, config.ini"
+        value={excludeGlobs}
+        onChange={(e) => onExcludeGlobsChange(e.target.value)}
+        className="w-full px-4 py-3 bg-gray-50 border border-gray-200 rounded-xl text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+      />
+      <p className="text-xs text-gray-500 mt-1">
+        相对于存档文件夹的通配符，用逗号分隔。* 匹配任意字符，** 匹配任意层目录；不含 / 的规则匹配任意位置的文件名。留空表示不限制
+      </p>
+    </div>
+  );
+}
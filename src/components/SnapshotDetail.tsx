@@ -1,5 +1,5 @@
-import { useState } from "react";
-import { Snapshot } from "../types";
+import { useState, useEffect } from "react";
+import { Snapshot, RestoreOutcome } from "../types";
 import { invoke } from "@tauri-apps/api/core";
 
 interface SnapshotDetailProps {
@@ -21,15 +21,31 @@ export default function SnapshotDetail({
   onRestoreSuccess,
 }: SnapshotDetailProps) {
   const [isRestoring, setIsRestoring] = useState(false);
+  const [thumbnail, setThumbnail] = useState<string | null>(null);
+
+  useEffect(() => {
+    setThumbnail(null);
+    if (!snapshot.thumbnail_path) return;
+    invoke<string>("load_snapshot_image_base64", { imagePath: snapshot.thumbnail_path })
+      .then(setThumbnail)
+      .catch(() => {});
+  }, [snapshot.thumbnail_path]);
 
   async function handleRestore() {
-    if (!confirm(`确定要恢复这个快照吗？\n这将会替换当前的存档文件：\n${snapshot.original_save_path}\n\n此操作不可撤销。`)) {
+    if (!confirm(`确定要恢复这个快照吗？\n这将会替换当前的存档文件：\n${snapshot.original_save_path}\n\n恢复前会自动为当前存档创建一个"恢复前快照"。`)) {
       return;
     }
 
     setIsRestoring(true);
     try {
-      await invoke("restore_snapshot", { snapshotId: snapshot.id });
+      let outcome = await invoke<RestoreOutcome>("restore_snapshot", { snapshotId: snapshot.id });
+      if (!outcome.restored) {
+        const messages = outcome.warnings.map(w => w.message).join("\n");
+        if (!confirm(`${messages}\n\n仍要强制恢复吗？`)) {
+          return;
+        }
+        outcome = await invoke<RestoreOutcome>("restore_snapshot", { snapshotId: snapshot.id, force: true });
+      }
       alert("快照恢复成功！");
       if (onRestoreSuccess) {
         onRestoreSuccess();
@@ -61,6 +77,15 @@ export default function SnapshotDetail({
             </button>
           </div>
           <div className="space-y-3">
+            {thumbnail && (
+              <img src={thumbnail} alt={snapshot.name} className="w-full max-w-sm rounded-lg border border-gray-200" />
+            )}
+            {snapshot.summary && (
+              <div>
+                <p className="text-sm font-medium text-gray-700 mb-1">存档摘要:</p>
+                <p className="text-sm text-gray-600">{snapshot.summary}</p>
+              </div>
+            )}
             <div>
               <p className="text-sm font-medium text-gray-700 mb-1">原始路径:</p>
               <p className="text-sm text-gray-600 break-all">{snapshot.original_save_path}</p>
@@ -39,6 +39,7 @@ export default function SnapshotList({
       filtered = filtered.filter((s) => 
         s.name.toLowerCase().includes(query) ||
         s.note?.toLowerCase().includes(query) ||
+        s.summary?.toLowerCase().includes(query) ||
         s.original_save_path.toLowerCase().includes(query) ||
         new Date(s.created_at).toLocaleString().toLowerCase().includes(query)
       );
@@ -300,6 +301,11 @@ export default function SnapshotList({
                     {s.name}
                   </div>
                 )}
+                {s.summary && (
+                  <div className="text-xs text-gray-500 truncate" title={s.summary}>
+                    {s.summary}
+                  </div>
+                )}
               </div>
             </div>
           ))
@@ -0,0 +1,52 @@
+// 添加和编辑游戏共用的 save_config 生成逻辑
+
+// 所有存档模式共有的配置项，与 save_config.rs 的 COMMON_KEYS 一致
+const COMMON_KEYS = ["version", "include_globs", "exclude_globs", "watch_backend"];
+
+// 早期（v0）配置中可能写成逗号分隔字符串的扩展名列表
+const LIST_KEYS = ["extensions", "include_extensions", "exclude_extensions", "container_extensions", "inner_extensions"];
+
+/** save_config 中该存档模式专有的部分（去掉通用字段）；无法解析时为空对象 */
+export function modeConfigOf(saveConfig: string | undefined): Record<string, unknown> {
+    if (!saveConfig) return {};
+    let config: Record<string, unknown>;
+    try {
+        config = JSON.parse(saveConfig);
+    } catch (e) {
+        return {};
+    }
+    if (typeof config !== "object" || config === null || Array.isArray(config)) return {};
+    for (const key of COMMON_KEYS) {
+        delete config[key];
+    }
+    for (const key of LIST_KEYS) {
+        const value = config[key];
+        if (typeof value === "string") {
+            config[key] = value.split(",").map(e => e.trim()).filter(e => e.length > 0);
+        }
+    }
+    return config;
+}
+
+/** 规则输入框中逗号或换行分隔的通配符 */
+function splitGlobs(value: string): string[] {
+    return value.split(/[,\n]/).map(g => g.trim()).filter(g => g.length > 0);
+}
+
+/** 在存档模式专有的配置上加入通用字段，生成保存到数据库的 save_config */
+export function buildSaveConfig(
+    modeConfig: Record<string, unknown>,
+    includeGlobs: string,
+    excludeGlobs: string,
+    usePolling: boolean,
+): string {
+    const include = splitGlobs(includeGlobs);
+    const exclude = splitGlobs(excludeGlobs);
+    return JSON.stringify({
+        version: 1,
+        ...modeConfig,
+        ...(include.length > 0 ? { include_globs: include } : {}),
+        ...(exclude.length > 0 ? { exclude_globs: exclude } : {}),
+        ...(usePolling ? { watch_backend: "poll" } : {}),
+    });
+}
@@ -7,6 +7,7 @@ export interface Game {
     cover_image?: string;
     save_mode?: string;  // 存档模式：single_file, folder, file_group, container
     save_config?: string;  // JSON 配置字符串
+    paused?: boolean;  // 暂停自动快照
 }
 
 export interface Snapshot {
@@ -17,6 +18,94 @@ export interface Snapshot {
     backup_save_path: string;
     note?: string;
     created_at: string;
+    thumbnail_path?: string;
+    kind?: string;  // 快照类型：auto, pre_restore, manual, startup, resume, tombstone
+    summary?: string;  // 存档内容摘要，如 RPG Maker 存档的地图、游戏时间和队伍
+}
+
+export interface WatchStatus {
+    game_id: string;
+    path: string;
+    watching: boolean;
+    backend: string;  // native, poll
+    last_event?: string;
+    last_error?: string;
+}
+
+export interface SavePreviewItem {
+    relative_path: string;
+    size: number;
+    modified?: string;
+    group?: string;  // 会被快照时所属的分组，被忽略时为空
+    reason?: string;  // 被忽略的原因
+}
+
+export interface SaveConfigPreview {
+    save_mode: string;
+    root: string;
+    items: SavePreviewItem[];
+    captured_count: number;
+    ignored_count: number;
+    group_count: number;
+    truncated: boolean;
+}
+
+export interface ObservedFile {
+    relative_path: string;
+    writes: number;
+    first_seen: string;
+    last_seen: string;
+    role: string;  // slot, system, temp
+}
+
+export interface ObserveStatus {
+    game_id: string;
+    started_at: string;
+    expires_at: string;
+    expired: boolean;  // 超过最长观察时间后不再记录写入
+    files: ObservedFile[];
+}
+
+export interface ConfigSuggestion {
+    save_mode: string;
+    save_config: string;
+    files: ObservedFile[];
+    notes: string[];
+}
+
+export interface DetectedEngine {
+    engine: string;
+    name: string;
+    evidence: string;  // 命中的特征文件
+    save_mode: string;
+    save_config: string;
+    save_folder_path?: string;
+}
+
+export interface SnapshotFailure {
+    game_id: string;  // 快捷键快照找不到运行中的游戏时为空
+    error: string;
+}
+
+export interface RestoreWarning {
+    code: string;  // game_running
+    message: string;
+}
+
+export interface RestoreOutcome {
+    restored: boolean;
+    pre_restore_snapshot_id?: string;
+    warnings: RestoreWarning[];
+}
+
+export interface RestorePreviewItem {
+    relative_path: string;
+    target_path: string;
+    action: "create" | "overwrite" | "unchanged" | "delete";
+    backup_size?: number;  // 要删除的文件在快照中没有副本
+    backup_modified?: string;
+    current_size?: number;
+    current_modified?: string;
 }
 
 export interface Screenshot {